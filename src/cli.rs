@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use crate::config::ConfigError;
+use crate::resources::SimulationConfig;
+use crate::save::{SaveError, SaveFile};
+
+// ===== COMMAND LINE ARGUMENTS =====
+
+pub const USAGE: &str = "\
+Usage: predators-and-prey [OPTIONS]
+
+Options:
+  --config <PATH>     Load simulation settings from a RON file
+  --load <PATH>       Start from a saved world (and its settings) instead of
+                      a fresh one
+  --save <PATH>       Headless: save the world here when the run ends.
+                      Windowed: file used by F5 (save) and F9 (load)
+  --newick <PATH>     Headless: write the run's phylogeny here when it ends.
+                      Windowed: file written by N
+  --headless          Run the simulation without a window and print a summary
+  --duration <SECS>   Simulated seconds to run in headless mode (default: 300)
+  --ticks <N>         Simulation ticks to run in headless mode
+  --seed <N>          Seed for the simulation RNG (default: random)
+  -h, --help          Print this help";
+
+/// Options parsed from the command line
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct CliArgs {
+    pub config_path: Option<PathBuf>,
+    pub load_path: Option<PathBuf>,
+    pub save_path: Option<PathBuf>,
+    pub newick_path: Option<PathBuf>,
+    pub headless: bool,
+    pub duration: Option<f32>,
+    pub ticks: Option<u64>,
+    pub seed: Option<u64>,
+    pub help: bool,
+}
+
+impl CliArgs {
+    /// Parse arguments, excluding the program name
+    pub fn parse<I>(args: I) -> Result<Self, String>
+    where
+        I: IntoIterator<Item = String>,
+    {
+        let mut parsed = CliArgs::default();
+        let mut args = args.into_iter();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--config" => parsed.config_path = Some(parse_value(&arg, args.next())?),
+                "--load" => parsed.load_path = Some(parse_value(&arg, args.next())?),
+                "--save" => parsed.save_path = Some(parse_value(&arg, args.next())?),
+                "--newick" => parsed.newick_path = Some(parse_value(&arg, args.next())?),
+                "--headless" => parsed.headless = true,
+                "--duration" => {
+                    let secs: f32 = parse_value(&arg, args.next())?;
+                    if !secs.is_finite() || secs <= 0.0 {
+                        return Err(format!("--duration must be positive, got {secs}"));
+                    }
+                    parsed.duration = Some(secs);
+                }
+                "--ticks" => parsed.ticks = Some(parse_value(&arg, args.next())?),
+                "--seed" => parsed.seed = Some(parse_value(&arg, args.next())?),
+                "-h" | "--help" => parsed.help = true,
+                other => return Err(format!("unknown argument '{other}'")),
+            }
+        }
+
+        if parsed.duration.is_some() && parsed.ticks.is_some() {
+            return Err("--duration and --ticks cannot be used together".into());
+        }
+        if !parsed.headless && (parsed.duration.is_some() || parsed.ticks.is_some()) {
+            return Err("--duration and --ticks require --headless".into());
+        }
+
+        Ok(parsed)
+    }
+
+    /// Load the simulation config (from --config if given, defaults otherwise)
+    /// and apply any overrides given on the command line
+    pub fn config(&self) -> Result<SimulationConfig, ConfigError> {
+        let mut config = match &self.config_path {
+            Some(path) => SimulationConfig::load(path)?,
+            None => SimulationConfig::default(),
+        };
+        if let Some(seed) = self.seed {
+            config.seed = Some(seed);
+        }
+        Ok(config)
+    }
+
+    /// Read the save given with --load, if any
+    pub fn load(&self) -> Result<Option<SaveFile>, SaveError> {
+        self.load_path.as_ref().map(SaveFile::read).transpose()
+    }
+}
+
+fn parse_value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    let value = value.ok_or_else(|| format!("{flag} requires a value"))?;
+    value
+        .parse()
+        .map_err(|_| format!("invalid value '{value}' for {flag}"))
+}
@@ -1,9 +1,12 @@
 use bevy::prelude::*;
 use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::resources::{MutationConfig, TraitMutation};
 
 // ===== COMPONENTS =====
 
-#[derive(Component, Clone)]
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Genome {
     pub speed: f32,
     pub size: f32,
@@ -13,63 +16,81 @@ pub struct Genome {
 }
 
 impl Genome {
-    pub fn random_plant() -> Self {
-        let mut rng = rand::rng();
-        Self {
-            speed: 0.0,
-            size: rng.random_range(0.5..1.5),
-            metabolism: rng.random_range(0.3..0.7),
-            reproduction_threshold: rng.random_range(80.0..120.0),
-            vision_range: 0.0,
-        }
-    }
+    /// Uniform crossover: each trait comes from one parent or the other
+    pub fn crossover(&self, other: &Genome, rng: &mut impl Rng) -> Self {
+        let mut pick = |a: f32, b: f32| if rng.random_bool(0.5) { a } else { b };
 
-    pub fn random_prey() -> Self {
-        let mut rng = rand::rng();
         Self {
-            speed: rng.random_range(50.0..150.0),
-            size: rng.random_range(1.0..2.0),
-            metabolism: rng.random_range(0.5..1.5),
-            reproduction_threshold: rng.random_range(60.0..100.0),
-            vision_range: rng.random_range(80.0..120.0),
+            speed: pick(self.speed, other.speed),
+            size: pick(self.size, other.size),
+            metabolism: pick(self.metabolism, other.metabolism),
+            reproduction_threshold: pick(self.reproduction_threshold, other.reproduction_threshold),
+            vision_range: pick(self.vision_range, other.vision_range),
         }
     }
 
-    pub fn random_predator() -> Self {
-        let mut rng = rand::rng();
-        Self {
-            speed: rng.random_range(80.0..180.0),
-            size: rng.random_range(1.5..3.0),
-            metabolism: rng.random_range(1.0..2.0),
-            reproduction_threshold: rng.random_range(80.0..140.0),
-            vision_range: rng.random_range(100.0..180.0),
-        }
-    }
+    /// An offspring's genome: each trait independently mutates with its own
+    /// rate and magnitude. Traits a species does not use (zero, like a
+    /// plant's speed) stay at zero.
+    pub fn mutated(&self, mutation: &MutationConfig, rng: &mut impl Rng) -> Self {
+        let mut mutate = |value: f32, settings: &TraitMutation| {
+            if value == 0.0 || !rng.random_bool(f64::from(settings.rate)) {
+                return value;
+            }
+            let change = rng.random_range(-settings.magnitude..=settings.magnitude);
+            (value * (1.0 + change)).clamp(settings.min, settings.max)
+        };
 
-    pub fn random_scavenger() -> Self {
-        let mut rng = rand::rng();
         Self {
-            speed: rng.random_range(60.0..120.0), // Slower than predators
-            size: rng.random_range(1.2..2.5),
-            metabolism: rng.random_range(0.6..1.2), // Lower metabolism
-            reproduction_threshold: rng.random_range(70.0..110.0),
-            vision_range: rng.random_range(120.0..200.0), // Good vision to spot corpses
+            speed: mutate(self.speed, &mutation.speed),
+            size: mutate(self.size, &mutation.size),
+            metabolism: mutate(self.metabolism, &mutation.metabolism),
+            reproduction_threshold: mutate(
+                self.reproduction_threshold,
+                &mutation.reproduction_threshold,
+            ),
+            vision_range: mutate(self.vision_range, &mutation.vision_range),
         }
     }
 }
 
+/// Ancestry of an organism. Ids are unique for the whole run (unlike
+/// `Entity`s, which get reused) and survive saving and loading.
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Lineage {
+    pub id: u64,
+    /// One parent for asexual births, two for sexual ones, none for founders
+    pub parents: Vec<u64>,
+    /// Founders are generation 0; offspring are one past their parents' highest generation
+    pub generation: u32,
+    /// Simulated seconds since the run started
+    pub birth_time: f32,
+    /// Spawned at setup or arrived from outside rather than born
+    pub founder: bool,
+}
+
+/// Which entry of `SimulationConfig::species` a living organism belongs to
+#[derive(Component, Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub struct SpeciesId(pub usize);
+
+// Behavior markers, one per `Behavior`, so systems can query by what an
+// organism does rather than which species it is
+
 #[derive(Component)]
-pub struct Plant;
+pub struct Producer;
 
 #[derive(Component)]
-pub struct Prey;
+pub struct Forager;
 
 #[derive(Component)]
-pub struct Predator;
+pub struct Hunter;
 
 #[derive(Component)]
 pub struct Scavenger;
 
+#[derive(Component)]
+pub struct Omnivore;
+
 #[derive(Component)]
 pub struct Energy(pub f32);
 
@@ -79,10 +100,11 @@ pub struct Age(pub f32);
 #[derive(Component)]
 pub struct Velocity(pub Vec2);
 
+/// What an animal that kills is chasing, or the catch it is feeding on
 #[derive(Component)]
 pub struct HuntTarget(pub Option<Entity>);
 
-#[derive(Component)]
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Stamina {
     pub current: f32,
     pub max: f32,
@@ -99,17 +121,23 @@ impl Default for Stamina {
     }
 }
 
-#[derive(Component)]
+#[derive(Component, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct Corpse {
+    /// What the organism was; drives its appearance and who eats it
+    pub species: SpeciesId,
     pub decay_timer: f32, // Time remaining before corpse despawns
     pub max_decay_time: f32,
+    /// Food left on the carcass; eaten bite by bite
+    pub biomass: f32,
 }
 
 impl Corpse {
-    pub fn new(max_decay_time: f32) -> Self {
+    pub fn new(species: SpeciesId, max_decay_time: f32, biomass: f32) -> Self {
         Self {
+            species,
             decay_timer: max_decay_time,
             max_decay_time,
+            biomass,
         }
     }
 }
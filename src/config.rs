@@ -0,0 +1,381 @@
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::resources::{ObstacleConfig, SimulationConfig, SoilConfig, TerrainConfig};
+
+// ===== CONFIG LOADING =====
+
+/// Fields that only matter when the world is first built. A running
+/// simulation keeps its current values for these when the file changes.
+pub const RESTART_ONLY_FIELDS: &[&str] = &["world_size", "tick_rate", "seed"];
+
+/// One field that differs between two configs, with both values as RON text
+#[derive(Debug, Clone, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: String,
+    pub new: String,
+    restart_only: bool,
+}
+
+impl FieldChange {
+    /// Restart-only fields, plus `species` when species are added, removed,
+    /// reordered or change behavior, `soil` when its cell size changes, and
+    /// `terrain` and `obstacles` when their layout changes (other edits to
+    /// these apply live)
+    pub fn requires_restart(&self) -> bool {
+        self.restart_only
+    }
+}
+
+/// Why a config file could not be used
+#[derive(Debug)]
+pub enum ConfigError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Parse {
+        path: PathBuf,
+        source: ron::error::SpannedError,
+    },
+    Invalid(Vec<String>),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => {
+                write!(f, "could not read config '{}': {source}", path.display())
+            }
+            ConfigError::Parse { path, source } => {
+                write!(f, "could not parse config '{}': {source}", path.display())
+            }
+            ConfigError::Invalid(problems) => {
+                write!(f, "invalid config:")?;
+                for problem in problems {
+                    write!(f, "\n  - {problem}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+impl SimulationConfig {
+    /// Load a RON config file. Missing fields keep their default values.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| ConfigError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let config = Self::from_ron(&text).map_err(|source| ConfigError::Parse {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn from_ron(text: &str) -> Result<Self, ron::error::SpannedError> {
+        ron::from_str(text)
+    }
+
+    /// Every field whose value differs between `self` and `other`
+    pub fn changed_fields(&self, other: &Self) -> Vec<FieldChange> {
+        let (Some(old), Some(new)) = (self.to_value_map(), other.to_value_map()) else {
+            return Vec::new();
+        };
+
+        old.iter()
+            .filter_map(|(key, old_value)| {
+                let new_value = new.get(key)?;
+                if old_value == new_value {
+                    return None;
+                }
+                let ron::Value::String(field) = key else {
+                    return None;
+                };
+                let restart_only = RESTART_ONLY_FIELDS.contains(&field.as_str())
+                    || (field == "species" && !self.species.same_layout(&other.species))
+                    || (field == "soil" && self.soil.cell_size != other.soil.cell_size)
+                    || (field == "terrain" && !self.terrain.same_layout(&other.terrain))
+                    || (field == "obstacles" && !self.obstacles.same_layout(&other.obstacles));
+                Some(FieldChange {
+                    restart_only,
+                    field: field.clone(),
+                    old: ron::to_string(old_value).unwrap_or_default(),
+                    new: ron::to_string(new_value).unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
+    /// Take every live-tunable value from `new`, keeping the restart-only
+    /// fields (see `FieldChange::requires_restart`) of the running world
+    pub fn apply_live(&mut self, new: &Self) {
+        let species = if self.species.same_layout(&new.species) {
+            new.species.clone()
+        } else {
+            self.species.clone()
+        };
+        let soil = SoilConfig {
+            cell_size: self.soil.cell_size,
+            ..new.soil.clone()
+        };
+        let terrain = TerrainConfig {
+            cell_size: self.terrain.cell_size,
+            patch_size: self.terrain.patch_size,
+            ..new.terrain.clone()
+        };
+        let obstacles = ObstacleConfig {
+            avoidance_distance: new.obstacles.avoidance_distance,
+            ..self.obstacles.clone()
+        };
+        *self = Self {
+            world_size: self.world_size,
+            tick_rate: self.tick_rate,
+            seed: self.seed,
+            species,
+            soil,
+            terrain,
+            obstacles,
+            ..new.clone()
+        };
+    }
+
+    fn to_value_map(&self) -> Option<ron::Map> {
+        let text = ron::to_string(self).ok()?;
+        match ron::from_str(&text).ok()? {
+            ron::Value::Map(map) => Some(map),
+            _ => None,
+        }
+    }
+
+    /// Check that the values describe a world the simulation can run,
+    /// reporting every problem rather than just the first
+    pub fn validate(&self) -> Result<(), ConfigError> {
+        let mut problems = Vec::new();
+
+        let world_size = self.world_size;
+        if !(world_size.x > 0.0 && world_size.y > 0.0 && world_size.is_finite()) {
+            problems.push(format!(
+                "world_size must be positive, got ({}, {})",
+                self.world_size.x, self.world_size.y
+            ));
+        }
+        if !(self.tick_rate.is_finite() && self.tick_rate > 0.0) {
+            problems.push(format!(
+                "tick_rate must be positive, got {}",
+                self.tick_rate
+            ));
+        }
+
+        let positive = [
+            ("max_age", self.max_age),
+            ("corpse_decay_time", self.corpse_decay_time),
+            ("mate_search_radius", self.mate_search_radius),
+            ("grid_cell_size", self.grid_cell_size),
+            ("soil.cell_size", self.soil.cell_size),
+            ("terrain.cell_size", self.terrain.cell_size),
+            ("terrain.patch_size", self.terrain.patch_size),
+            (
+                "obstacles.avoidance_distance",
+                self.obstacles.avoidance_distance,
+            ),
+            ("pathfinding.cell_size", self.pathfinding.cell_size),
+            ("climate.year_length", self.climate.year_length),
+            ("climate.day_length", self.climate.day_length),
+            ("crowding.flocking_radius", self.crowding.flocking_radius),
+            ("crowding.forager_spacing", self.crowding.forager_spacing),
+            ("crowding.hunter_spacing", self.crowding.hunter_spacing),
+            (
+                "crowding.scavenger_spacing",
+                self.crowding.scavenger_spacing,
+            ),
+            ("crowding.carcass_appetite", self.crowding.carcass_appetite),
+        ];
+        for (name, value) in positive {
+            if !(value.is_finite() && value > 0.0) {
+                problems.push(format!("{name} must be positive, got {value}"));
+            }
+        }
+
+        if self.pathfinding.max_cached_fields == 0 {
+            problems.push("pathfinding.max_cached_fields must be at least 1".to_string());
+        }
+        if self.crowding.max_hunters_per_target == 0 {
+            problems.push("crowding.max_hunters_per_target must be at least 1".to_string());
+        }
+
+        let boost = self.low_population_reproduction_boost;
+        if !(boost.is_finite() && boost >= 0.0) {
+            problems.push(format!(
+                "low_population_reproduction_boost must not be negative, got {boost}"
+            ));
+        }
+
+        if !(0.0..=1.0).contains(&self.mating_energy_cost) {
+            problems.push(format!(
+                "mating_energy_cost must be between 0 and 1, got {}",
+                self.mating_energy_cost
+            ));
+        }
+
+        let soil = [
+            ("soil.initial_nutrients", self.soil.initial_nutrients),
+            ("soil.half_saturation", self.soil.half_saturation),
+            ("soil.uptake", self.soil.uptake),
+        ];
+        for (name, value) in soil {
+            if !(value.is_finite() && value >= 0.0) {
+                problems.push(format!("{name} must not be negative, got {value}"));
+            }
+        }
+        if !(0.0..=1.0).contains(&self.soil.waste_fraction) {
+            problems.push(format!(
+                "soil.waste_fraction must be between 0 and 1, got {}",
+                self.soil.waste_fraction
+            ));
+        }
+
+        for (name, biome) in self.terrain.biomes() {
+            let effects = [
+                ("growth", biome.growth),
+                ("respawn", biome.respawn),
+                ("speed", biome.speed),
+                ("visibility", biome.visibility),
+            ];
+            for (effect, value) in effects {
+                if !(value.is_finite() && value >= 0.0) {
+                    problems.push(format!(
+                        "terrain.{name}.{effect} must not be negative, got {value}"
+                    ));
+                }
+            }
+        }
+
+        let densities = [
+            ("obstacles.lake_density", self.obstacles.lake_density),
+            ("obstacles.rock_density", self.obstacles.rock_density),
+        ];
+        for (name, value) in densities {
+            if !(value.is_finite() && value >= 0.0) {
+                problems.push(format!("{name} must not be negative, got {value}"));
+            }
+        }
+        let radii = [
+            ("obstacles.lake_radius", self.obstacles.lake_radius),
+            ("obstacles.rock_radius", self.obstacles.rock_radius),
+        ];
+        for (name, (min, max)) in radii {
+            if !(min > 0.0 && min <= max && max.is_finite()) {
+                problems.push(format!(
+                    "{name} must satisfy 0 < min <= max, got ({min}, {max})"
+                ));
+            }
+        }
+        for (index, obstacle) in self.obstacles.placed.iter().enumerate() {
+            if !(obstacle.radius.is_finite() && obstacle.radius > 0.0) {
+                problems.push(format!(
+                    "obstacles.placed[{index}].radius must be positive, got {}",
+                    obstacle.radius
+                ));
+            }
+        }
+
+        let climate = &self.climate;
+        let sun_up = (
+            climate.daylight_fraction - climate.daylight_variation,
+            climate.daylight_fraction + climate.daylight_variation,
+        );
+        if !(climate.daylight_variation >= 0.0 && sun_up.0 > 0.0 && sun_up.1 <= 1.0) {
+            problems.push(format!(
+                "climate daylight_fraction +/- daylight_variation must stay within (0, 1], got {}..{}",
+                sun_up.0, sun_up.1
+            ));
+        }
+        let fractions = [
+            ("climate.sunlight_variation", climate.sunlight_variation),
+            ("climate.night_light", climate.night_light),
+            ("climate.drought_chance", climate.drought_chance),
+            ("climate.wet_chance", climate.wet_chance),
+            ("night.tint_strength", self.night.tint_strength),
+        ];
+        for (name, value) in fractions {
+            if !(0.0..=1.0).contains(&value) {
+                problems.push(format!("{name} must be between 0 and 1, got {value}"));
+            }
+        }
+        if climate.drought_chance + climate.wet_chance > 1.0 {
+            problems.push(format!(
+                "climate.drought_chance + climate.wet_chance must not exceed 1, got {}",
+                climate.drought_chance + climate.wet_chance
+            ));
+        }
+        let non_negative = [
+            ("climate.noon_sunlight", climate.noon_sunlight),
+            ("climate.cold_metabolism", climate.cold_metabolism),
+            ("climate.drought_rainfall", climate.drought_rainfall),
+            ("climate.wet_rainfall", climate.wet_rainfall),
+            ("night.vision", self.night.vision),
+            ("night.nocturnal_vision", self.night.nocturnal_vision),
+            ("night.rest_energy", self.night.rest_energy),
+            ("night.rest_speed", self.night.rest_speed),
+            ("night.rest_cohesion", self.night.rest_cohesion),
+        ];
+        for (name, value) in non_negative {
+            if !(value.is_finite() && value >= 0.0) {
+                problems.push(format!("{name} must not be negative, got {value}"));
+            }
+        }
+
+        for (name, mutation) in self.mutation.traits() {
+            if !(0.0..=1.0).contains(&mutation.rate) {
+                problems.push(format!(
+                    "mutation.{name}.rate must be between 0 and 1, got {}",
+                    mutation.rate
+                ));
+            }
+            if !(mutation.magnitude.is_finite() && mutation.magnitude >= 0.0) {
+                problems.push(format!(
+                    "mutation.{name}.magnitude must not be negative, got {}",
+                    mutation.magnitude
+                ));
+            }
+            if !(mutation.min >= 0.0 && mutation.min <= mutation.max) {
+                problems.push(format!(
+                    "mutation.{name} bounds must satisfy 0 <= min <= max, got {}..{}",
+                    mutation.min, mutation.max
+                ));
+            }
+        }
+
+        problems.extend(self.species.problems());
+        problems.extend(self.food_web.problems(&self.species));
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ConfigError::Invalid(problems))
+        }
+    }
+}
+
+/// Serialize a `Vec2` as a plain `(x, y)` tuple
+pub mod vec2_as_array {
+    use bevy::math::Vec2;
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Vec2, serializer: S) -> Result<S::Ok, S::Error> {
+        (value.x, value.y).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec2, D::Error> {
+        let (x, y) = <(f32, f32)>::deserialize(deserializer)?;
+        Ok(Vec2::new(x, y))
+    }
+}
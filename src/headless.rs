@@ -0,0 +1,126 @@
+use bevy::prelude::*;
+use bevy::time::TimeUpdateStrategy;
+use std::time::{Duration, Instant};
+
+use crate::EcosystemPlugin;
+use crate::cli::CliArgs;
+use crate::phylogeny::BirthDeathLedger;
+use crate::resources::*;
+use crate::save::{PendingWorldLoad, SaveFile};
+use crate::soil::SoilNutrients;
+use crate::systems::{climate_line, population_line};
+
+// ===== HEADLESS MODE =====
+
+/// Simulated seconds to run when neither --duration nor --ticks is given
+pub const DEFAULT_DURATION: f32 = 300.0;
+
+/// Build an app that runs the full simulation without a window or any rendering.
+/// Every update advances simulated time by exactly one fixed tick.
+pub fn create_headless_app(config: SimulationConfig) -> App {
+    let tick = Duration::from_secs_f64(1.0 / config.tick_rate);
+
+    let mut app = App::new();
+    app.add_plugins((MinimalPlugins, EcosystemPlugin::new(config)))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(tick));
+    app
+}
+
+/// Run the simulation for the requested number of ticks and print a summary,
+/// optionally starting from a save and saving the final world
+pub fn run(args: &CliArgs, config: SimulationConfig, save: Option<SaveFile>) -> AppExit {
+    let ticks = args.ticks.unwrap_or_else(|| {
+        (f64::from(args.duration.unwrap_or(DEFAULT_DURATION)) * config.tick_rate).round() as u64
+    });
+
+    let mut app = create_headless_app(config);
+    if let Some(save) = save {
+        app.insert_resource(PendingWorldLoad(save));
+    }
+    app.finish();
+    app.cleanup();
+
+    let started = Instant::now();
+    for _ in 0..ticks {
+        app.update();
+    }
+
+    print_summary(app.world(), ticks, started.elapsed());
+
+    if let Some(path) = &args.save_path {
+        if let Err(err) = SaveFile::capture(app.world_mut()).write(path) {
+            eprintln!("error: {err}");
+            return AppExit::error();
+        }
+        println!("Saved world to {}", path.display());
+    }
+    if let Some(path) = &args.newick_path {
+        let ledger = app.world().resource::<BirthDeathLedger>();
+        let species = &app.world().resource::<SimulationConfig>().species;
+        if let Err(err) = ledger.write_newick(path, species) {
+            eprintln!(
+                "error: could not write phylogeny '{}': {err}",
+                path.display()
+            );
+            return AppExit::error();
+        }
+        println!(
+            "Wrote phylogeny of {} organisms to {}",
+            ledger.entries.len(),
+            path.display()
+        );
+    }
+    AppExit::Success
+}
+
+fn print_summary(world: &World, ticks: u64, wall_time: Duration) {
+    let stats = world.resource::<PopulationStats>();
+    let seed = world.resource::<SimulationRng>().seed();
+    let history = world.resource::<SimulationHistory>();
+    let simulated = ticks as f64 / world.resource::<SimulationConfig>().tick_rate;
+
+    println!("\n========== Headless Run Summary ==========");
+    println!(
+        "Ran {ticks} ticks ({simulated:.1}s simulated) in {:.2}s wall time",
+        wall_time.as_secs_f64()
+    );
+    println!("Seed: {seed}");
+    println!("Final population: {}", population_line(stats));
+
+    if let Some(latest) = history.snapshots.last() {
+        println!("Total Energy: {:.1}", latest.total_energy);
+        let speeds: Vec<String> = latest
+            .species
+            .iter()
+            .filter(|s| s.avg_speed > 0.0)
+            .map(|s| format!("{}={:.1}", s.name, s.avg_speed))
+            .collect();
+        println!("Avg Speeds: {}", speeds.join(", "));
+    }
+    println!("Climate: {}", climate_line(world.resource::<Climate>()));
+    let soil = world.resource::<SoilNutrients>();
+    println!(
+        "Soil nutrients: {:.1} (returned {:.1}, taken up by plants {:.1})",
+        soil.total(),
+        soil.deposited,
+        soil.absorbed
+    );
+
+    let peaks: Vec<String> = stats
+        .species
+        .iter()
+        .enumerate()
+        .map(|(index, (name, _))| {
+            let peak = history
+                .snapshots
+                .iter()
+                .filter_map(|s| s.species.get(index))
+                .map(|species| species.count)
+                .max()
+                .unwrap_or(0);
+            format!("{name}={peak}")
+        })
+        .collect();
+    println!("Peak population: {}", peaks.join(", "));
+    println!("==========================================");
+}
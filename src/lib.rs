@@ -0,0 +1,22 @@
+pub mod cli;
+pub mod components;
+pub mod config;
+pub mod headless;
+pub mod obstacles;
+pub mod pathfinding;
+pub mod phylogeny;
+pub mod plugins;
+pub mod resources;
+pub mod save;
+pub mod soil;
+pub mod spatial;
+pub mod species;
+pub mod systems;
+pub mod terrain;
+pub mod utils;
+
+#[cfg(test)]
+#[allow(clippy::module_inception)]
+mod tests;
+
+pub use plugins::{ConfigHotReloadPlugin, EcosystemPlugin, EcosystemRenderPlugin};
@@ -1,64 +1,77 @@
 use bevy::prelude::*;
 
-mod components;
-mod resources;
-mod systems;
-mod utils;
+use predators_and_prey::cli::{self, CliArgs};
+use predators_and_prey::headless;
+use predators_and_prey::resources::{NewickPath, SavePath};
+use predators_and_prey::save::PendingWorldLoad;
+use predators_and_prey::{ConfigHotReloadPlugin, EcosystemPlugin, EcosystemRenderPlugin};
 
-#[cfg(test)]
-mod tests;
+fn main() -> AppExit {
+    let args = match CliArgs::parse(std::env::args().skip(1)) {
+        Ok(args) => args,
+        Err(err) => {
+            eprintln!("error: {err}\n\n{}", cli::USAGE);
+            return AppExit::error();
+        }
+    };
 
-use resources::*;
-use systems::*;
+    if args.help {
+        println!("{}", cli::USAGE);
+        return AppExit::Success;
+    }
 
-fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                title: "Predators and Prey - Ecology Simulator".into(),
-                resolution: (1280, 720).into(),
-                canvas: Some("#game-container".into()),
-                fit_canvas_to_parent: true,
-                prevent_default_event_handling: false,
-                ..default()
-            }),
+    let save = match args.load() {
+        Ok(save) => save,
+        Err(err) => {
+            eprintln!("error: {err}");
+            return AppExit::error();
+        }
+    };
+
+    // A loaded world brings its own settings
+    let config = match &save {
+        Some(save) => save.config.clone(),
+        None => match args.config() {
+            Ok(config) => config,
+            Err(err) => {
+                eprintln!("error: {err}");
+                return AppExit::error();
+            }
+        },
+    };
+
+    if args.headless {
+        return headless::run(&args, config, save);
+    }
+
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(Window {
+            title: "Predators and Prey - Ecology Simulator".into(),
+            resolution: (1280, 720).into(),
+            canvas: Some("#game-container".into()),
+            fit_canvas_to_parent: true,
+            prevent_default_event_handling: false,
             ..default()
-        }))
-        .init_resource::<SimulationConfig>()
-        .init_resource::<PopulationStats>()
-        .init_resource::<SunlightLevel>()
-        .init_resource::<ConsoleOutput>()
-        .insert_resource(SimulationHistory {
-            snapshots: Vec::new(),
-            record_interval: 1.0,
-            time_since_last_record: 0.0,
-        })
-        .add_systems(Startup, setup)
-        .add_systems(Update, camera_controls_system)
-        .add_systems(
-            Update,
-            (
-                sunlight_cycle_system,
-                plant_growth_system,
-                plant_respawn_system,
-                immigration_system,
-                prey_movement_system,
-                predator_hunting_system,
-                scavenger_movement_system,
-                eating_system,
-                energy_consumption_system,
-                age_system,
-                reproduction_system,
-                death_system,
-                corpse_decay_system,
-                update_population_stats,
-                record_history_system,
-                console_output_system,
-                visual_polish_system,
-                ui_system,
-                draw_graphs_system,
-            )
-                .chain(),
-        )
-        .run();
+        }),
+        ..default()
+    }))
+    .add_plugins((EcosystemPlugin::new(config), EcosystemRenderPlugin));
+
+    if let Some(save) = save {
+        app.insert_resource(PendingWorldLoad(save));
+    }
+    if let Some(path) = args.save_path.clone().or(args.load_path.clone()) {
+        app.insert_resource(SavePath(path));
+    }
+    if let Some(path) = args.newick_path.clone() {
+        app.insert_resource(NewickPath(path));
+    }
+
+    // Tune balance on the fly: edits to the config file apply without a restart
+    if let Some(path) = args.config_path {
+        app.add_plugins(ConfigHotReloadPlugin { path });
+    }
+
+    app.run()
 }
@@ -0,0 +1,166 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use serde::{Deserialize, Serialize};
+
+use crate::config::vec2_as_array;
+use crate::resources::ObstacleConfig;
+use crate::species::random_position;
+use crate::utils::wrapped_direction;
+
+// ===== OBSTACLES =====
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum ObstacleKind {
+    Rock,
+    Lake,
+}
+
+/// A round patch of the world nothing can enter
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Obstacle {
+    pub kind: ObstacleKind,
+    #[serde(with = "vec2_as_array")]
+    pub center: Vec2,
+    pub radius: f32,
+}
+
+/// Random points tried before `random_open_position` settles for pushing
+/// its last try out of the way
+const OPEN_POSITION_ATTEMPTS: usize = 64;
+
+/// Every obstacle in the world: those placed in the config, then those
+/// scattered from the seed when the world is built
+#[derive(Resource, Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Obstacles {
+    #[serde(with = "vec2_as_array")]
+    world_size: Vec2,
+    obstacles: Vec<Obstacle>,
+}
+
+impl Obstacles {
+    pub fn generate(world_size: Vec2, config: &ObstacleConfig, seed: u64) -> Self {
+        // A generator of its own, so obstacles leave `SimulationRng` untouched
+        let mut rng = StdRng::seed_from_u64(seed ^ 0x0B57_AC1E);
+        let mut obstacles = config.placed.clone();
+        let mut scatter = |kind, density: f32, (min, max): (f32, f32)| {
+            let count = (density * world_size.x * world_size.y / 1_000_000.0).round() as usize;
+            for _ in 0..count {
+                obstacles.push(Obstacle {
+                    kind,
+                    center: random_position(world_size, &mut rng),
+                    radius: rng.random_range(min..=max),
+                });
+            }
+        };
+        scatter(ObstacleKind::Lake, config.lake_density, config.lake_radius);
+        scatter(ObstacleKind::Rock, config.rock_density, config.rock_radius);
+        Self {
+            world_size,
+            obstacles,
+        }
+    }
+
+    pub fn world_size(&self) -> Vec2 {
+        self.world_size
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &Obstacle> {
+        self.obstacles.iter()
+    }
+
+    /// Whether `position` lies inside any obstacle, across the world edges
+    pub fn contains(&self, position: Vec2) -> bool {
+        self.obstacles.iter().any(|obstacle| {
+            wrapped_direction(position, obstacle.center, &self.world_size).length()
+                < obstacle.radius
+        })
+    }
+
+    /// Whether the straight (shortest, wrapped) line from `from` to `to`
+    /// misses every obstacle
+    pub fn clear_path(&self, from: Vec2, to: Vec2) -> bool {
+        let path = wrapped_direction(from, to, &self.world_size);
+        let length_squared = path.length_squared();
+        self.obstacles.iter().all(|obstacle| {
+            let to_center = wrapped_direction(from, obstacle.center, &self.world_size);
+            let along = if length_squared > 0.0 {
+                (to_center.dot(path) / length_squared).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            to_center.distance(path * along) >= obstacle.radius
+        })
+    }
+
+    /// A uniformly random point outside every obstacle
+    pub fn random_open_position(&self, rng: &mut impl Rng) -> Vec2 {
+        let mut position = random_position(self.world_size, rng);
+        for _ in 1..OPEN_POSITION_ATTEMPTS {
+            if !self.contains(position) {
+                return position;
+            }
+            position = random_position(self.world_size, rng);
+        }
+        self.nearest_open(position)
+    }
+
+    /// `position`, or the closest point just outside the obstacles it is in
+    pub fn nearest_open(&self, mut position: Vec2) -> Vec2 {
+        // Overlapping obstacles can push a point from one into another
+        for _ in 0..self.obstacles.len() {
+            let Some(obstacle) = self.obstacles.iter().find(|obstacle| {
+                wrapped_direction(position, obstacle.center, &self.world_size).length()
+                    < obstacle.radius
+            }) else {
+                break;
+            };
+            let outward = -wrapped_direction(position, obstacle.center, &self.world_size);
+            let direction = outward.try_normalize().unwrap_or(Vec2::X);
+            position = obstacle.center + direction * (obstacle.radius + 0.5);
+            position = wrapped(position, self.world_size);
+        }
+        position
+    }
+
+    /// Move an organism that ended up inside an obstacle back out to its edge
+    pub fn keep_out(&self, translation: &mut Vec3) {
+        let open = self.nearest_open(translation.truncate());
+        translation.x = open.x;
+        translation.y = open.y;
+    }
+
+    /// `desired` bent around obstacles within `distance` of `position`: the
+    /// part heading into an obstacle turns into sliding along its edge, the
+    /// more so the closer the edge
+    pub fn steer(&self, position: Vec2, desired: Vec2, distance: f32) -> Vec2 {
+        let mut steered = desired;
+        for obstacle in &self.obstacles {
+            let to_center = wrapped_direction(position, obstacle.center, &self.world_size);
+            let gap = to_center.length() - obstacle.radius;
+            if gap > distance {
+                continue;
+            }
+            let inward = to_center.normalize_or_zero();
+            let heading_in = steered.dot(inward);
+            if heading_in <= 0.0 {
+                continue;
+            }
+            let closeness = 1.0 - (gap / distance).clamp(0.0, 1.0);
+            let tangent = inward.perp();
+            let side = if steered.dot(tangent) >= 0.0 {
+                tangent
+            } else {
+                -tangent
+            };
+            steered += (side - inward * (1.0 + closeness)) * heading_in * closeness;
+        }
+        steered
+    }
+}
+
+/// `position` wrapped into the world, which is centred on the origin
+fn wrapped(position: Vec2, world_size: Vec2) -> Vec2 {
+    (position + world_size / 2.0).rem_euclid(world_size) - world_size / 2.0
+}
@@ -0,0 +1,183 @@
+use bevy::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::{Arc, RwLock};
+
+use crate::obstacles::Obstacles;
+use crate::resources::PathfindingConfig;
+use crate::utils::{CellLayout, wrapped_direction};
+
+// ===== FLOW FIELDS =====
+
+/// Path length from every cell to one goal cell, in world units;
+/// infinite where the goal cannot be reached
+type DistanceField = Arc<Vec<f32>>;
+
+/// Headings around obstacles, on a coarse grid over the torus world.
+///
+/// A cell is blocked when its centre lies inside an obstacle. The distance
+/// field to a goal cell is built the first time anyone heads there and
+/// cached until the obstacles or the grid change, so everyone chasing
+/// toward the same place shares one search. Fields depend only on the
+/// obstacles and the goal, so it doesn't matter which system builds them.
+#[derive(Resource, Default)]
+pub struct FlowFields {
+    layout: CellLayout,
+    obstacles: Obstacles,
+    blocked: Vec<bool>,
+    max_cached: usize,
+    cache: RwLock<HashMap<usize, DistanceField>>,
+}
+
+impl FlowFields {
+    pub fn new(obstacles: &Obstacles, config: &PathfindingConfig) -> Self {
+        let layout = CellLayout::new(obstacles.world_size(), config.cell_size);
+        let blocked = (0..layout.len())
+            .map(|index| obstacles.contains(layout.center(index)))
+            .collect();
+        Self {
+            layout,
+            obstacles: obstacles.clone(),
+            blocked,
+            max_cached: config.max_cached_fields,
+            cache: RwLock::default(),
+        }
+    }
+
+    /// Whether these fields were built for `obstacles` and `config`
+    pub fn matches(&self, obstacles: &Obstacles, config: &PathfindingConfig) -> bool {
+        self.obstacles == *obstacles
+            && self.max_cached == config.max_cached_fields
+            && self.layout == CellLayout::new(obstacles.world_size(), config.cell_size)
+    }
+
+    /// Unit heading from `from` toward `to`: straight there when nothing is
+    /// in the way, otherwise toward the neighbouring cell on the shortest
+    /// path around the obstacles
+    pub fn heading(&self, from: Vec2, to: Vec2) -> Vec2 {
+        let world_size = self.layout.world_size;
+        let direct = wrapped_direction(from, to, &world_size).normalize_or_zero();
+        if self.layout.is_empty() || self.obstacles.clear_path(from, to) {
+            return direct;
+        }
+        let (start, goal) = (self.layout.index_of(from), self.layout.index_of(to));
+        if start == goal {
+            return direct;
+        }
+
+        let field = self.field(goal);
+        self.neighbours(start)
+            .filter(|&(next, _)| field[next].is_finite())
+            .min_by(|&(a, a_step), &(b, b_step)| {
+                (field[a] + a_step).total_cmp(&(field[b] + b_step))
+            })
+            .map_or(direct, |(next, _)| {
+                wrapped_direction(from, self.layout.center(next), &world_size).normalize_or_zero()
+            })
+    }
+
+    /// Distance fields currently cached
+    pub fn cached_fields(&self) -> usize {
+        self.cache.read().map_or(0, |cache| cache.len())
+    }
+
+    fn field(&self, goal: usize) -> DistanceField {
+        if let Some(field) = self
+            .cache
+            .read()
+            .ok()
+            .and_then(|cache| cache.get(&goal).cloned())
+        {
+            return field;
+        }
+
+        let field = Arc::new(self.search(goal));
+        if self.max_cached > 0
+            && let Ok(mut cache) = self.cache.write()
+        {
+            if cache.len() >= self.max_cached {
+                cache.clear();
+            }
+            cache.insert(goal, field.clone());
+        }
+        field
+    }
+
+    /// Dijkstra outward from `goal` over open cells
+    fn search(&self, goal: usize) -> Vec<f32> {
+        let mut distances = vec![f32::INFINITY; self.layout.len()];
+        let mut frontier = BinaryHeap::new();
+        distances[goal] = 0.0;
+        frontier.push(Frontier(0.0, goal));
+
+        while let Some(Frontier(distance, cell)) = frontier.pop() {
+            if distance > distances[cell] {
+                continue;
+            }
+            for (next, step) in self.neighbours(cell) {
+                let through = distance + step;
+                if through < distances[next] {
+                    distances[next] = through;
+                    frontier.push(Frontier(through, next));
+                }
+            }
+        }
+        distances
+    }
+
+    /// Open cells one step from `cell` across the world edges, with the
+    /// length of the step. Diagonal steps may not cut a blocked corner.
+    fn neighbours(&self, cell: usize) -> impl Iterator<Item = (usize, f32)> + '_ {
+        let (columns, rows) = (self.layout.columns as i64, self.layout.rows as i64);
+        let (column, row) = (cell as i64 % columns, cell as i64 / columns);
+        let index = move |dx: i64, dy: i64| {
+            ((row + dy).rem_euclid(rows) * columns + (column + dx).rem_euclid(columns)) as usize
+        };
+        let size = self.layout.cell_size;
+
+        [
+            (1, 0),
+            (-1, 0),
+            (0, 1),
+            (0, -1),
+            (1, 1),
+            (1, -1),
+            (-1, 1),
+            (-1, -1),
+        ]
+        .into_iter()
+        .filter(move |&(dx, dy)| {
+            !self.blocked[index(dx, dy)]
+                && (dx == 0
+                    || dy == 0
+                    || (!self.blocked[index(dx, 0)] && !self.blocked[index(0, dy)]))
+        })
+        .map(move |(dx, dy)| {
+            let step = Vec2::new(dx as f32 * size.x, dy as f32 * size.y).length();
+            (index(dx, dy), step)
+        })
+    }
+}
+
+/// A cell waiting in the search, ordered so the nearest pops first
+struct Frontier(f32, usize);
+
+impl PartialEq for Frontier {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Frontier {}
+
+impl PartialOrd for Frontier {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Frontier {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.0.total_cmp(&self.0).then(other.1.cmp(&self.1))
+    }
+}
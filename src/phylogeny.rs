@@ -0,0 +1,177 @@
+use bevy::platform::collections::HashMap;
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::path::Path;
+
+use crate::components::*;
+use crate::species::SpeciesRegistry;
+
+// ===== BIRTH / DEATH LEDGER =====
+
+/// One organism's life as recorded in the ledger
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LedgerEntry {
+    /// Index into the run's species, named only when exporting
+    pub species: SpeciesId,
+    pub parents: Vec<u64>,
+    pub generation: u32,
+    pub birth_time: f32,
+    pub death_time: Option<f32>,
+    pub genome: Genome,
+}
+
+/// Every organism that ever lived in this run, keyed by `Lineage::id`
+#[derive(Resource, Default, Clone, Debug, Serialize, Deserialize)]
+pub struct BirthDeathLedger {
+    pub entries: BTreeMap<u64, LedgerEntry>,
+    /// Living organisms, so deaths can be recorded after the entity is gone
+    #[serde(skip)]
+    living: HashMap<Entity, u64>,
+}
+
+impl PartialEq for BirthDeathLedger {
+    fn eq(&self, other: &Self) -> bool {
+        self.entries == other.entries
+    }
+}
+
+impl BirthDeathLedger {
+    /// Record a newly spawned organism. Organisms already in the ledger
+    /// (e.g. restored from a save) are only re-attached to their entity.
+    pub fn record_birth(
+        &mut self,
+        entity: Entity,
+        lineage: &Lineage,
+        species: SpeciesId,
+        genome: &Genome,
+    ) {
+        self.living.insert(entity, lineage.id);
+        self.entries
+            .entry(lineage.id)
+            .or_insert_with(|| LedgerEntry {
+                species,
+                parents: lineage.parents.clone(),
+                generation: lineage.generation,
+                birth_time: lineage.birth_time,
+                death_time: None,
+                genome: genome.clone(),
+            });
+    }
+
+    /// Record the death of a living organism; unknown entities are ignored
+    pub fn record_death(&mut self, entity: Entity, time: f32) {
+        let Some(id) = self.living.remove(&entity) else {
+            return;
+        };
+        if let Some(entry) = self.entries.get_mut(&id) {
+            entry.death_time.get_or_insert(time);
+        }
+    }
+
+    /// The full ancestry as a Newick tree with NHX trait annotations.
+    ///
+    /// Each organism hangs off its first parent (a sexual offspring's second
+    /// parent is kept as the `mate` annotation). Branch lengths are seconds
+    /// between a parent's birth and its offspring's; founders hang off a
+    /// common root at time zero. Species are named from `registry`.
+    pub fn to_newick(&self, registry: &SpeciesRegistry) -> String {
+        let mut children: BTreeMap<u64, Vec<u64>> = BTreeMap::new();
+        let mut roots = Vec::new();
+        for (&id, entry) in &self.entries {
+            match entry.parents.first() {
+                Some(parent) if self.entries.contains_key(parent) => {
+                    children.entry(*parent).or_default().push(id);
+                }
+                _ => roots.push(id),
+            }
+        }
+        children.insert(u64::MAX, roots);
+
+        // Iterative so deep lineages cannot overflow the stack
+        enum Visit {
+            Enter(u64),
+            Comma,
+            Exit(u64),
+        }
+
+        let mut newick = String::new();
+        let mut stack = vec![Visit::Enter(u64::MAX)];
+        while let Some(visit) = stack.pop() {
+            match visit {
+                Visit::Enter(id) => match children.get(&id) {
+                    Some(kids) if !kids.is_empty() => {
+                        newick.push('(');
+                        stack.push(Visit::Exit(id));
+                        for (index, &kid) in kids.iter().enumerate().rev() {
+                            stack.push(Visit::Enter(kid));
+                            if index > 0 {
+                                stack.push(Visit::Comma);
+                            }
+                        }
+                    }
+                    _ => self.write_node(&mut newick, registry, id),
+                },
+                Visit::Comma => newick.push(','),
+                Visit::Exit(id) => {
+                    newick.push(')');
+                    self.write_node(&mut newick, registry, id);
+                }
+            }
+        }
+        newick.push(';');
+        newick
+    }
+
+    fn write_node(&self, newick: &mut String, registry: &SpeciesRegistry, id: u64) {
+        let Some(entry) = self.entries.get(&id) else {
+            newick.push_str("run");
+            return;
+        };
+
+        let parent_birth = entry
+            .parents
+            .first()
+            .and_then(|parent| self.entries.get(parent))
+            .map_or(0.0, |parent| parent.birth_time);
+        let genome = &entry.genome;
+        let species = registry
+            .0
+            .get(entry.species.0)
+            .map_or("unknown", |species| species.name.as_str());
+
+        let _ = write!(
+            newick,
+            "{}_{id}:{:.3}[&&NHX:S={}:generation={}:born={:.3}",
+            species,
+            (entry.birth_time - parent_birth).max(0.0),
+            species,
+            entry.generation,
+            entry.birth_time,
+        );
+        if let Some(death_time) = entry.death_time {
+            let _ = write!(newick, ":died={death_time:.3}");
+        }
+        if let Some(mate) = entry.parents.get(1) {
+            let _ = write!(newick, ":mate={mate}");
+        }
+        let _ = write!(
+            newick,
+            ":speed={:.3}:size={:.3}:metabolism={:.3}:reproduction_threshold={:.3}:vision_range={:.3}]",
+            genome.speed,
+            genome.size,
+            genome.metabolism,
+            genome.reproduction_threshold,
+            genome.vision_range,
+        );
+    }
+
+    pub fn write_newick(
+        &self,
+        path: impl AsRef<Path>,
+        registry: &SpeciesRegistry,
+    ) -> std::io::Result<()> {
+        std::fs::write(path, self.to_newick(registry))
+    }
+}
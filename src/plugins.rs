@@ -0,0 +1,224 @@
+use bevy::prelude::*;
+use std::path::PathBuf;
+
+use crate::obstacles::Obstacles;
+use crate::pathfinding::FlowFields;
+use crate::phylogeny::BirthDeathLedger;
+use crate::resources::*;
+use crate::save::PendingWorldLoad;
+use crate::soil::SoilNutrients;
+use crate::spatial::SpatialGrid;
+use crate::systems::*;
+use crate::terrain::BiomeMap;
+
+// ===== PLUGINS =====
+
+/// Stages of a simulation tick, run in this order in `FixedUpdate`.
+/// `Render` runs in `Update`, once per frame.
+#[derive(SystemSet, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SimulationSet {
+    /// Clock, climate, plant growth and new arrivals
+    Environment,
+    /// Building the neighbour lookups everyone senses through and the paths
+    /// they travel by
+    Sense,
+    Move,
+    /// Eating
+    Interact,
+    /// Metabolism, ageing, reproduction and death
+    Lifecycle,
+    Stats,
+    /// Sprites, UI panel and graphs
+    Render,
+}
+
+/// The simulation itself: resources, the initial population and the
+/// fixed-timestep system chain. Adds nothing that needs a window, so the
+/// game, the tests and headless runs all build exactly the same simulation.
+#[derive(Default)]
+pub struct EcosystemPlugin {
+    pub config: SimulationConfig,
+}
+
+impl EcosystemPlugin {
+    pub fn new(config: SimulationConfig) -> Self {
+        Self { config }
+    }
+}
+
+impl Plugin for EcosystemPlugin {
+    fn build(&self, app: &mut App) {
+        let rng = SimulationRng::from_config(&self.config);
+        let seed = rng.seed();
+        info!("Simulation seed: {seed}");
+        let obstacles = Obstacles::generate(self.config.world_size, &self.config.obstacles, seed);
+
+        app.insert_resource(Time::<Fixed>::from_hz(self.config.tick_rate))
+            .insert_resource(self.config.clone())
+            .insert_resource(rng)
+            .init_resource::<PopulationStats>()
+            .init_resource::<SunlightLevel>()
+            .insert_resource(Climate::new(&self.config.climate))
+            .insert_resource(SoilNutrients::new(
+                self.config.world_size,
+                &self.config.soil,
+            ))
+            .insert_resource(BiomeMap::generate(
+                self.config.world_size,
+                &self.config.terrain,
+                seed,
+            ))
+            .insert_resource(FlowFields::new(&obstacles, &self.config.pathfinding))
+            .insert_resource(obstacles)
+            .init_resource::<SimulationClock>()
+            .init_resource::<LineageTracker>()
+            .init_resource::<BirthDeathLedger>()
+            .init_resource::<SpatialGrid>()
+            .init_resource::<TickSeed>()
+            .init_resource::<ConsoleOutput>()
+            .insert_resource(SimulationHistory {
+                snapshots: Vec::new(),
+                record_interval: 1.0,
+                time_since_last_record: 0.0,
+            })
+            .add_systems(
+                Startup,
+                (
+                    setup.run_if(not(resource_exists::<PendingWorldLoad>)),
+                    apply_pending_world_load,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                PreUpdate,
+                apply_pending_world_load.run_if(resource_exists::<PendingWorldLoad>),
+            )
+            .configure_sets(
+                FixedUpdate,
+                (
+                    SimulationSet::Environment,
+                    SimulationSet::Sense,
+                    SimulationSet::Move,
+                    SimulationSet::Interact,
+                    SimulationSet::Lifecycle,
+                    SimulationSet::Stats,
+                )
+                    .chain(),
+            )
+            // Systems sharing `SimulationRng` or spawning entities are always
+            // ordered, so the draws and entity ids are the same on every run
+            .add_systems(
+                FixedUpdate,
+                (
+                    (
+                        simulation_clock_system,
+                        draw_tick_seed_system,
+                        climate_system,
+                        plant_respawn_system,
+                        immigration_system,
+                    )
+                        .chain(),
+                    plant_growth_system.after(climate_system),
+                )
+                    .in_set(SimulationSet::Environment),
+            )
+            .add_systems(
+                FixedUpdate,
+                (rebuild_spatial_grid_system, sync_flow_fields_system).in_set(SimulationSet::Sense),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    (
+                        prey_movement_system,
+                        omnivore_movement_system,
+                        scavenger_movement_system,
+                    ),
+                    // Hunters chase where everything else has just moved to
+                    predator_hunting_system,
+                )
+                    .chain()
+                    .in_set(SimulationSet::Move),
+            )
+            .add_systems(
+                FixedUpdate,
+                // Again, so eating sees where everyone moved to
+                (rebuild_spatial_grid_system, eating_system)
+                    .chain()
+                    .in_set(SimulationSet::Interact),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    (energy_consumption_system, age_system),
+                    reproduction_system,
+                    death_system,
+                    corpse_decay_system,
+                )
+                    .chain()
+                    .in_set(SimulationSet::Lifecycle),
+            )
+            .add_systems(
+                FixedUpdate,
+                (update_population_stats, record_history_system)
+                    .chain()
+                    .in_set(SimulationSet::Stats),
+            )
+            .add_systems(Update, console_output_system)
+            .add_observer(record_birth_observer)
+            .add_observer(record_corpse_observer)
+            .add_observer(record_despawn_observer);
+    }
+}
+
+/// Everything needed to watch the simulation: camera, sprites, UI panel and graphs
+pub struct EcosystemRenderPlugin;
+
+impl Plugin for EcosystemRenderPlugin {
+    fn build(&self, app: &mut App) {
+        app.configure_sets(Update, SimulationSet::Render.after(console_output_system))
+            .init_resource::<SavePath>()
+            .init_resource::<NewickPath>()
+            .add_systems(Startup, setup_view)
+            .add_systems(
+                Update,
+                (
+                    camera_controls_system,
+                    save_load_hotkeys_system,
+                    newick_export_hotkey_system,
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    (
+                        terrain_background_system,
+                        obstacle_shape_system,
+                        night_shade_system,
+                    ),
+                    (
+                        attach_sprites_system,
+                        corpse_sprite_system,
+                        corpse_fade_system,
+                        visual_polish_system,
+                    )
+                        .chain(),
+                    ui_system,
+                    draw_graphs_system,
+                )
+                    .in_set(SimulationSet::Render),
+            );
+    }
+}
+
+/// Watches the config file and applies edits to the running simulation
+pub struct ConfigHotReloadPlugin {
+    pub path: PathBuf,
+}
+
+impl Plugin for ConfigHotReloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(ConfigWatcher::new(self.path.clone()))
+            .add_systems(Update, config_hot_reload_system);
+    }
+}
@@ -1,103 +1,869 @@
 use bevy::prelude::*;
+use rand::rngs::StdRng;
+use rand::{Rng, RngCore, SeedableRng};
+use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
+
+use crate::components::Lineage;
+use crate::obstacles::Obstacle;
+use crate::species::{FoodWeb, SpeciesRegistry};
+use crate::terrain::Biome;
+use crate::utils::splitmix64;
 
 // ===== RESOURCES =====
 
-#[derive(Resource)]
+#[derive(Resource, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
 pub struct SimulationConfig {
+    #[serde(with = "crate::config::vec2_as_array")]
     pub world_size: Vec2,
-    pub initial_plants: usize,
-    pub initial_prey: usize,
-    pub initial_predators: usize,
-    pub initial_scavengers: usize,
-    pub plant_energy_from_sun: f32,
-    pub prey_energy_from_plant: f32,
-    pub predator_energy_from_prey: f32,
-    pub scavenger_energy_from_corpse: f32,
-    pub plant_respawn_rate: f32,
-    pub max_plants: usize,
+    /// Age in seconds at which animals die of old age
+    pub max_age: f32,
+    /// Seconds a corpse lingers before it is removed
+    pub corpse_decay_time: f32,
+    /// Animal species below this population reproduce faster
+    pub low_population_threshold: usize,
+    /// Reproduction rate multiplier applied below `low_population_threshold`
+    pub low_population_reproduction_boost: f32,
+    /// Simulation ticks per second (the `FixedUpdate` rate)
+    pub tick_rate: f64,
+    /// Seed for `SimulationRng`; a random seed is picked when unset
+    pub seed: Option<u64>,
+    /// How offspring genomes drift from their parent's
+    pub mutation: MutationConfig,
+    /// Animals need a mate of their own species to reproduce
+    /// (producers always spread asexually)
+    pub sexual_reproduction: bool,
+    /// How far an animal looks for a mate in sexual mode
+    pub mate_search_radius: f32,
+    /// Fraction of each parent's energy passed to the offspring in sexual mode
+    pub mating_energy_cost: f32,
+    /// Approximate side length of a spatial grid cell; roughly the typical
+    /// neighbour search radius works best
+    pub grid_cell_size: f32,
+    /// Every species in the world; ids are positions in this list
+    pub species: SpeciesRegistry,
+    /// Who eats whom, by species name
+    pub food_web: FoodWeb,
+    /// How nutrients cycle between the soil, plants and animals
+    pub soil: SoilConfig,
+    /// The biome map and how each biome affects life on it
+    pub terrain: TerrainConfig,
+    /// Rocks and lakes nothing can enter
+    pub obstacles: ObstacleConfig,
+    /// How animals find their way around obstacles
+    pub pathfinding: PathfindingConfig,
+    /// Days, seasons and the weather of each year
+    pub climate: ClimateConfig,
+    /// How animals see and behave after dark, and how dark the scene gets
+    pub night: NightConfig,
+    /// How animals keep their distance from each other and share prey
+    pub crowding: CrowdingConfig,
 }
 
 impl Default for SimulationConfig {
     fn default() -> Self {
         Self {
             world_size: Vec2::new(4800.0, 3200.0),
-            initial_plants: 600,
-            initial_prey: 160,
-            initial_predators: 32,
-            initial_scavengers: 48,
-            plant_energy_from_sun: 0.5,
-            prey_energy_from_plant: 30.0,
-            predator_energy_from_prey: 50.0,
-            scavenger_energy_from_corpse: 35.0,
-            plant_respawn_rate: 2.0,
-            max_plants: 1200,
+            max_age: 300.0,
+            corpse_decay_time: 30.0,
+            low_population_threshold: 10,
+            low_population_reproduction_boost: 2.0,
+            tick_rate: 60.0,
+            seed: None,
+            mutation: MutationConfig::default(),
+            sexual_reproduction: false,
+            mate_search_radius: 80.0,
+            mating_energy_cost: 0.3,
+            grid_cell_size: 100.0,
+            species: SpeciesRegistry::default(),
+            food_web: FoodWeb::default(),
+            soil: SoilConfig::default(),
+            terrain: TerrainConfig::default(),
+            obstacles: ObstacleConfig::default(),
+            pathfinding: PathfindingConfig::default(),
+            climate: ClimateConfig::default(),
+            night: NightConfig::default(),
+            crowding: CrowdingConfig::default(),
+        }
+    }
+}
+
+/// How one genome trait changes between parent and offspring
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TraitMutation {
+    /// Chance that an offspring's value differs from its parent's
+    pub rate: f32,
+    /// Largest change, as a fraction of the parent's value
+    pub magnitude: f32,
+    /// Mutated values are clamped to `min..=max`
+    pub min: f32,
+    pub max: f32,
+}
+
+impl TraitMutation {
+    pub const fn new(rate: f32, magnitude: f32, min: f32, max: f32) -> Self {
+        Self {
+            rate,
+            magnitude,
+            min,
+            max,
+        }
+    }
+}
+
+/// How nutrients move between the soil and living things
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SoilConfig {
+    /// Approximate side length of a soil cell; only read when the world is built
+    pub cell_size: f32,
+    /// Nutrients in every cell when the world is built
+    pub initial_nutrients: f32,
+    /// Nutrient level at which plants grow at half their sunlit rate
+    pub half_saturation: f32,
+    /// Nutrients a plant takes up per unit of energy it grows
+    pub uptake: f32,
+    /// Fraction of the energy animals burn that returns to the soil as waste
+    pub waste_fraction: f32,
+}
+
+impl Default for SoilConfig {
+    fn default() -> Self {
+        Self {
+            cell_size: 200.0,
+            initial_nutrients: 40.0,
+            half_saturation: 10.0,
+            uptake: 0.5,
+            waste_fraction: 0.1,
+        }
+    }
+}
+
+impl SoilConfig {
+    /// How well plants grow on `nutrients`, from 0 (barren) towards 1
+    pub fn fertility(&self, nutrients: f32) -> f32 {
+        if nutrients <= 0.0 {
+            0.0
+        } else {
+            nutrients / (nutrients + self.half_saturation)
+        }
+    }
+}
+
+/// Layout of the biome map and each biome's effects
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct TerrainConfig {
+    /// Approximate side length of a biome cell; only read when the world is built
+    pub cell_size: f32,
+    /// Typical width of a patch of one biome; only read when the world is built
+    pub patch_size: f32,
+    pub grassland: BiomeConfig,
+    pub forest: BiomeConfig,
+    pub desert: BiomeConfig,
+    pub marsh: BiomeConfig,
+}
+
+impl Default for TerrainConfig {
+    fn default() -> Self {
+        Self {
+            cell_size: 100.0,
+            patch_size: 800.0,
+            grassland: BiomeConfig::new(1.0, 1.0, 1.0, 1.0, (0.16, 0.22, 0.12)),
+            forest: BiomeConfig::new(1.3, 0.8, 0.75, 0.6, (0.07, 0.15, 0.08)),
+            desert: BiomeConfig::new(0.3, 0.3, 1.0, 1.3, (0.3, 0.26, 0.16)),
+            marsh: BiomeConfig::new(0.9, 0.7, 0.6, 0.9, (0.1, 0.17, 0.2)),
+        }
+    }
+}
+
+impl TerrainConfig {
+    pub fn biome(&self, biome: Biome) -> &BiomeConfig {
+        match biome {
+            Biome::Grassland => &self.grassland,
+            Biome::Forest => &self.forest,
+            Biome::Desert => &self.desert,
+            Biome::Marsh => &self.marsh,
+        }
+    }
+
+    /// Whether both configs generate the same biome map
+    pub fn same_layout(&self, other: &Self) -> bool {
+        self.cell_size == other.cell_size && self.patch_size == other.patch_size
+    }
+
+    /// Each biome's settings, by field name
+    pub fn biomes(&self) -> [(&'static str, &BiomeConfig); 4] {
+        [
+            ("grassland", &self.grassland),
+            ("forest", &self.forest),
+            ("desert", &self.desert),
+            ("marsh", &self.marsh),
+        ]
+    }
+}
+
+/// Multipliers for organisms in one biome, relative to open grassland
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BiomeConfig {
+    /// Plant growth rate
+    pub growth: f32,
+    /// Chance that an arriving plant takes root here
+    pub respawn: f32,
+    /// Animal movement speed
+    pub speed: f32,
+    /// How far animals standing here can see
+    pub visibility: f32,
+    /// Background colour as (red, green, blue)
+    pub color: (f32, f32, f32),
+}
+
+impl BiomeConfig {
+    pub const fn new(
+        growth: f32,
+        respawn: f32,
+        speed: f32,
+        visibility: f32,
+        color: (f32, f32, f32),
+    ) -> Self {
+        Self {
+            growth,
+            respawn,
+            speed,
+            visibility,
+            color,
+        }
+    }
+}
+
+/// Where obstacles are and how animals avoid them. Everything but
+/// `avoidance_distance` is only read when the world is built.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ObstacleConfig {
+    /// Obstacles at fixed places
+    pub placed: Vec<Obstacle>,
+    /// Lakes scattered at random, per 1000 × 1000 of world area
+    pub lake_density: f32,
+    pub lake_radius: (f32, f32),
+    /// Rocks scattered at random, per 1000 × 1000 of world area
+    pub rock_density: f32,
+    pub rock_radius: (f32, f32),
+    /// How close to an obstacle's edge animals start steering around it
+    pub avoidance_distance: f32,
+}
+
+impl Default for ObstacleConfig {
+    fn default() -> Self {
+        Self {
+            placed: Vec::new(),
+            lake_density: 0.25,
+            lake_radius: (150.0, 300.0),
+            rock_density: 0.75,
+            rock_radius: (30.0, 80.0),
+            avoidance_distance: 40.0,
         }
     }
 }
 
+impl ObstacleConfig {
+    /// Whether both configs build the same obstacles
+    pub fn same_layout(&self, other: &Self) -> bool {
+        Self {
+            avoidance_distance: other.avoidance_distance,
+            ..self.clone()
+        } == *other
+    }
+}
+
+/// The grid animals route around obstacles on
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PathfindingConfig {
+    /// Approximate side length of a pathfinding cell; smaller cells find
+    /// narrower passes but take longer to search
+    pub cell_size: f32,
+    /// Goals whose paths are remembered before the cache starts over
+    pub max_cached_fields: usize,
+}
+
+impl Default for PathfindingConfig {
+    fn default() -> Self {
+        Self {
+            cell_size: 50.0,
+            max_cached_fields: 256,
+        }
+    }
+}
+
+/// Days, seasons and years. Temperatures are in degrees Celsius.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ClimateConfig {
+    /// Seconds in a year of four seasons, starting with spring
+    pub year_length: f32,
+    /// Seconds from one sunrise to the next
+    pub day_length: f32,
+    /// Fraction of the day the sun is up in spring and autumn
+    pub daylight_fraction: f32,
+    /// How much longer than that the sun is up at midsummer (and shorter
+    /// at midwinter), as a fraction of the day
+    pub daylight_variation: f32,
+    /// Sunlight at noon in spring and autumn
+    pub noon_sunlight: f32,
+    /// How much stronger the midsummer sun is (and weaker the midwinter
+    /// sun), as a fraction of `noon_sunlight`
+    pub sunlight_variation: f32,
+    /// Light at night, as a fraction of the day's noon sunlight
+    pub night_light: f32,
+    /// Average temperature over a year
+    pub mean_temperature: f32,
+    /// Degrees warmer at midsummer and colder at midwinter
+    pub temperature_variation: f32,
+    /// Degrees between the coldest and the warmest time of day
+    pub daily_temperature_swing: f32,
+    /// Plants stop growing at or below this temperature
+    pub plant_min_temperature: f32,
+    /// Plants grow at full speed at or above this temperature
+    pub plant_optimal_temperature: f32,
+    /// Animals burn energy this much faster, per degree below
+    /// `comfort_temperature`
+    pub cold_metabolism: f32,
+    pub comfort_temperature: f32,
+    /// Chance that a new year is a drought, or a wet year
+    pub drought_chance: f32,
+    pub wet_chance: f32,
+    /// Plant growth in a drought year and a wet year, relative to a normal one
+    pub drought_rainfall: f32,
+    pub wet_rainfall: f32,
+    /// Degrees warmer than usual in a drought year
+    pub drought_warming: f32,
+}
+
+impl Default for ClimateConfig {
+    fn default() -> Self {
+        Self {
+            year_length: 240.0,
+            day_length: 20.0,
+            daylight_fraction: 0.6,
+            daylight_variation: 0.15,
+            noon_sunlight: 1.0,
+            sunlight_variation: 0.3,
+            night_light: 0.4,
+            mean_temperature: 12.0,
+            temperature_variation: 15.0,
+            daily_temperature_swing: 6.0,
+            plant_min_temperature: 0.0,
+            plant_optimal_temperature: 12.0,
+            cold_metabolism: 0.02,
+            comfort_temperature: 10.0,
+            drought_chance: 0.15,
+            wet_chance: 0.15,
+            drought_rainfall: 0.5,
+            wet_rainfall: 1.4,
+            drought_warming: 3.0,
+        }
+    }
+}
+
+/// Night behaviour. Effects scale with darkness, from none at noon to full
+/// once the sun is down.
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NightConfig {
+    /// Vision range in full darkness, relative to daylight
+    pub vision: f32,
+    /// The same for nocturnal species
+    pub nocturnal_vision: f32,
+    /// Once the sun is down, foragers with at least this fraction of their
+    /// reproduction threshold in energy stop foraging and rest in groups
+    pub rest_energy: f32,
+    /// Speed of resting foragers, relative to their usual speed
+    pub rest_speed: f32,
+    /// How much more strongly resting foragers pull together
+    pub rest_cohesion: f32,
+    /// sRGB colour laid over the scene at night, and its opacity in full
+    /// darkness
+    pub tint: (f32, f32, f32),
+    pub tint_strength: f32,
+}
+
+impl NightConfig {
+    /// Vision range multiplier at `darkness` (0 at noon, 1 after sunset)
+    pub fn vision(&self, darkness: f32, nocturnal: bool) -> f32 {
+        let dark = if nocturnal {
+            self.nocturnal_vision
+        } else {
+            self.vision
+        };
+        1.0 + (dark - 1.0) * darkness
+    }
+}
+
+impl Default for NightConfig {
+    fn default() -> Self {
+        Self {
+            vision: 0.6,
+            nocturnal_vision: 1.3,
+            rest_energy: 0.5,
+            rest_speed: 0.3,
+            rest_cohesion: 3.0,
+            tint: (0.02, 0.03, 0.12),
+            tint_strength: 0.55,
+        }
+    }
+}
+
+/// Spacing between animals of one species, and how hunters share prey
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CrowdingConfig {
+    /// Foragers flock with neighbours this close (or as far as they can see
+    /// while resting)
+    pub flocking_radius: f32,
+    /// Foragers, hunters and scavengers closer than this to one of their own
+    /// species push apart
+    pub forager_spacing: f32,
+    pub hunter_spacing: f32,
+    pub scavenger_spacing: f32,
+    /// Hunters look for other prey once more than this many chase theirs
+    pub max_hunters_per_target: usize,
+    /// Hunters stay on a carcass until their energy reaches this multiple of
+    /// their reproduction threshold
+    pub carcass_appetite: f32,
+}
+
+impl Default for CrowdingConfig {
+    fn default() -> Self {
+        Self {
+            flocking_radius: 60.0,
+            forager_spacing: 30.0,
+            hunter_spacing: 50.0,
+            scavenger_spacing: 40.0,
+            max_hunters_per_target: 3,
+            carcass_appetite: 1.5,
+        }
+    }
+}
+
+/// Per-trait mutation settings applied whenever an offspring is born
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MutationConfig {
+    pub speed: TraitMutation,
+    pub size: TraitMutation,
+    pub metabolism: TraitMutation,
+    pub reproduction_threshold: TraitMutation,
+    pub vision_range: TraitMutation,
+}
+
+impl Default for MutationConfig {
+    fn default() -> Self {
+        Self {
+            speed: TraitMutation::new(0.1, 0.1, 10.0, 400.0),
+            size: TraitMutation::new(0.1, 0.1, 0.2, 6.0),
+            metabolism: TraitMutation::new(0.1, 0.1, 0.1, 4.0),
+            reproduction_threshold: TraitMutation::new(0.1, 0.1, 20.0, 300.0),
+            vision_range: TraitMutation::new(0.1, 0.1, 20.0, 400.0),
+        }
+    }
+}
+
+impl MutationConfig {
+    /// Each trait's settings, by field name
+    pub fn traits(&self) -> [(&'static str, &TraitMutation); 5] {
+        [
+            ("speed", &self.speed),
+            ("size", &self.size),
+            ("metabolism", &self.metabolism),
+            ("reproduction_threshold", &self.reproduction_threshold),
+            ("vision_range", &self.vision_range),
+        ]
+    }
+}
+
+/// The single source of randomness for the simulation. Every system and
+/// genome constructor draws from it, so the same seed and tick count always
+/// reproduce the same world.
+#[derive(Resource)]
+pub struct SimulationRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl SimulationRng {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+
+    /// Seed from the config, falling back to a fresh random seed
+    pub fn from_config(config: &SimulationConfig) -> Self {
+        Self::new(config.seed.unwrap_or_else(|| rand::rng().random()))
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+impl RngCore for SimulationRng {
+    fn next_u32(&mut self) -> u32 {
+        self.rng.next_u32()
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.rng.next_u64()
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        self.rng.fill_bytes(dst)
+    }
+}
+
+/// Simulated seconds since the run started. Unlike `Time`, it is saved
+/// with the world and keeps counting after a load.
+#[derive(Resource, Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SimulationClock {
+    pub elapsed: f32,
+}
+
+/// Hands out `Lineage` records with run-unique ids
+#[derive(Resource, Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LineageTracker {
+    pub next_id: u64,
+}
+
+impl LineageTracker {
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// Lineage for an organism spawned at setup or arriving from outside
+    pub fn founder(&mut self, birth_time: f32) -> Lineage {
+        Lineage {
+            id: self.next_id(),
+            parents: Vec::new(),
+            generation: 0,
+            birth_time,
+            founder: true,
+        }
+    }
+
+    /// Lineage for an organism born to `parents`
+    pub fn offspring(&mut self, parents: &[&Lineage], birth_time: f32) -> Lineage {
+        Lineage {
+            id: self.next_id(),
+            parents: parents.iter().map(|parent| parent.id).collect(),
+            generation: parents
+                .iter()
+                .map(|parent| parent.generation + 1)
+                .max()
+                .unwrap_or(1),
+            birth_time,
+            founder: false,
+        }
+    }
+}
+
+/// Seed drawn from `SimulationRng` once per tick for systems that use
+/// `EntityRng`, so they need no mutable access to the shared RNG
+#[derive(Resource, Default, Clone, Copy)]
+pub struct TickSeed(pub u64);
+
+impl TickSeed {
+    /// RNG for one entity in one system; `stream` keeps systems that share
+    /// the tick seed from drawing identical numbers
+    pub fn entity_rng(self, stream: u64, entity: Entity) -> EntityRng {
+        EntityRng::new(self.0 ^ stream.wrapping_mul(0xA076_1D64_78BD_642F), entity)
+    }
+}
+
+/// Small, fast RNG (SplitMix64) for a single entity within a single tick.
+/// Its numbers depend only on the seed and the entity, not on which thread
+/// runs it, so parallel iteration stays deterministic.
+pub struct EntityRng(u64);
+
+impl EntityRng {
+    pub fn new(seed: u64, entity: Entity) -> Self {
+        Self(seed ^ entity.to_bits().wrapping_mul(0x9E37_79B9_7F4A_7C15))
+    }
+}
+
+impl RngCore for EntityRng {
+    fn next_u32(&mut self) -> u32 {
+        (self.next_u64() >> 32) as u32
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        splitmix64(self.0)
+    }
+
+    fn fill_bytes(&mut self, dst: &mut [u8]) {
+        rand::rand_core::impls::fill_bytes_via_next(self, dst)
+    }
+}
+
+/// Living organisms of each species, in registry order
 #[derive(Resource, Default, Clone)]
 pub struct PopulationStats {
-    pub plants: usize,
-    pub prey: usize,
-    pub predators: usize,
-    pub scavengers: usize,
+    pub species: Vec<(String, usize)>,
 }
 
-#[derive(Resource)]
+impl PopulationStats {
+    /// Population of the named species; zero for unknown names
+    pub fn count(&self, name: &str) -> usize {
+        self.species
+            .iter()
+            .find(|(species, _)| species == name)
+            .map_or(0, |&(_, count)| count)
+    }
+}
+
+/// How strongly the sun shines right now, set from the `Climate`
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SunlightLevel {
     pub intensity: f32,
-    pub cycle_time: f32,
 }
 
 impl Default for SunlightLevel {
     fn default() -> Self {
-        Self {
-            intensity: 1.0,
-            cycle_time: 0.0,
-        }
+        Self { intensity: 1.0 }
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Season {
+    #[default]
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+/// The weather of a whole year, drawn when the year begins
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum YearKind {
+    #[default]
+    Normal,
+    Drought,
+    Wet,
+}
+
+/// The time of day and year and the weather, updated every tick
+#[derive(Resource, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Climate {
+    /// Years completed since the run started
+    pub year: u32,
+    pub year_kind: YearKind,
+    pub season: Season,
+    /// How far through the year, from 0 to 1
+    pub year_progress: f32,
+    /// How far through the day, from 0 (sunrise) to 1
+    pub day_progress: f32,
+    /// Height of the sun, from 0 (below the horizon) to 1 (noon)
+    pub daylight: f32,
+    /// Degrees Celsius
+    pub temperature: f32,
+    /// Plant growth rate from temperature and rainfall, relative to a mild
+    /// normal year
+    pub growth: f32,
+    /// Animal energy use from the cold, relative to comfortable weather
+    pub metabolism: f32,
+}
+
+impl Climate {
+    /// The first moment of a normal year
+    pub fn new(config: &ClimateConfig) -> Self {
+        let mut climate = Self::default();
+        climate.update(0.0, config);
+        climate
+    }
+
+    /// Move to `elapsed` seconds into the run, keeping this year's weather
+    pub fn update(&mut self, elapsed: f32, config: &ClimateConfig) {
+        self.year_progress = (elapsed / config.year_length).fract();
+        self.season = match (self.year_progress * 4.0) as u32 {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        };
+        self.day_progress = (elapsed / config.day_length).fract();
+
+        // The sun is up for the first part of each day, longer in summer
+        let seasonal = self.seasonal();
+        let sun_up = config.daylight_fraction + config.daylight_variation * seasonal;
+        self.daylight = if self.day_progress < sun_up {
+            (std::f32::consts::PI * self.day_progress / sun_up).sin()
+        } else {
+            0.0
+        };
+
+        let warming = match self.year_kind {
+            YearKind::Drought => config.drought_warming,
+            _ => 0.0,
+        };
+        self.temperature = config.mean_temperature
+            + config.temperature_variation * seasonal
+            + config.daily_temperature_swing * (self.daylight - 0.5)
+            + warming;
+
+        let warmth = if config.plant_optimal_temperature > config.plant_min_temperature {
+            (self.temperature - config.plant_min_temperature)
+                / (config.plant_optimal_temperature - config.plant_min_temperature)
+        } else if self.temperature > config.plant_min_temperature {
+            1.0
+        } else {
+            0.0
+        };
+        let rainfall = match self.year_kind {
+            YearKind::Normal => 1.0,
+            YearKind::Drought => config.drought_rainfall,
+            YearKind::Wet => config.wet_rainfall,
+        };
+        self.growth = warmth.clamp(0.0, 1.0) * rainfall;
+        self.metabolism =
+            1.0 + config.cold_metabolism * (config.comfort_temperature - self.temperature).max(0.0);
+    }
+
+    /// 0 at noon, rising to 1 at sunset and staying there until sunrise
+    pub fn darkness(&self) -> f32 {
+        1.0 - self.daylight
+    }
+
+    /// Whether the sun is down
+    pub fn is_night(&self) -> bool {
+        self.daylight <= 0.0
+    }
+
+    /// Hour on a 24-hour clock, with sunrise at 06:00
+    pub fn hour(&self) -> f32 {
+        (6.0 + 24.0 * self.day_progress) % 24.0
+    }
+
+    /// Sunlight for the current time of day and year
+    pub fn sunlight(&self, config: &ClimateConfig) -> f32 {
+        let noon = config.noon_sunlight * (1.0 + config.sunlight_variation * self.seasonal());
+        noon * (config.night_light + (1.0 - config.night_light) * self.daylight)
+    }
+
+    /// 1 at midsummer, -1 at midwinter, 0 in mid spring and mid autumn
+    fn seasonal(&self) -> f32 {
+        (std::f32::consts::TAU * (self.year_progress - 0.125)).sin()
+    }
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SimulationSnapshot {
     pub _time: f32,
-    pub plant_count: usize,
-    pub prey_count: usize,
-    pub predator_count: usize,
-    pub scavenger_count: usize,
     pub total_energy: f32,
-    pub avg_plant_age: f32,
-    pub avg_prey_age: f32,
-    pub avg_predator_age: f32,
-    pub avg_prey_speed: f32,
-    pub avg_predator_speed: f32,
+    /// Nutrients in the soil across the whole world
+    pub soil_nutrients: f32,
+    /// One entry per species, in registry order
+    pub species: Vec<SpeciesSnapshot>,
 }
 
-impl Default for SimulationSnapshot {
-    fn default() -> Self {
+impl SimulationSnapshot {
+    pub fn species(&self, name: &str) -> Option<&SpeciesSnapshot> {
+        self.species.iter().find(|species| species.name == name)
+    }
+}
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SpeciesSnapshot {
+    pub name: String,
+    pub count: usize,
+    pub avg_age: f32,
+    pub avg_speed: f32,
+    pub generations: GenerationStats,
+}
+
+/// How many generations a species has gone through
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct GenerationStats {
+    pub max: u32,
+    pub mean: f32,
+}
+
+impl GenerationStats {
+    pub fn from_lineages<'a>(lineages: impl Iterator<Item = &'a Lineage>) -> Self {
+        let (count, sum, max) = lineages.fold((0, 0u64, 0), |(count, sum, max), lineage| {
+            (
+                count + 1,
+                sum + u64::from(lineage.generation),
+                max.max(lineage.generation),
+            )
+        });
         Self {
-            _time: 0.0,
-            plant_count: 0,
-            prey_count: 0,
-            predator_count: 0,
-            scavenger_count: 0,
-            total_energy: 0.0,
-            avg_plant_age: 0.0,
-            avg_prey_age: 0.0,
-            avg_predator_age: 0.0,
-            avg_prey_speed: 0.0,
-            avg_predator_speed: 0.0,
+            max,
+            mean: if count > 0 {
+                sum as f32 / count as f32
+            } else {
+                0.0
+            },
         }
     }
 }
 
-#[derive(Resource, Default)]
+#[derive(Resource, Default, Clone, Debug, PartialEq, Serialize, Deserialize)]
 pub struct SimulationHistory {
     pub snapshots: Vec<SimulationSnapshot>,
     pub record_interval: f32,
     pub time_since_last_record: f32,
 }
 
+/// Polls the config file the simulation was started from and applies
+/// edits to the running simulation
+#[derive(Resource)]
+pub struct ConfigWatcher {
+    pub path: PathBuf,
+    pub poll_interval: f32,
+    pub time_since_poll: f32,
+    pub last_contents: Option<String>,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        let path = path.into();
+        let last_contents = std::fs::read_to_string(&path).ok();
+        Self {
+            path,
+            poll_interval: 1.0,
+            time_since_poll: 0.0,
+            last_contents,
+        }
+    }
+}
+
+/// Where the F5/F9 hotkeys save and load the world
+#[derive(Resource)]
+pub struct SavePath(pub PathBuf);
+
+impl Default for SavePath {
+    fn default() -> Self {
+        Self(PathBuf::from("world.save.ron"))
+    }
+}
+
+/// Where the N hotkey writes the phylogenetic tree
+#[derive(Resource)]
+pub struct NewickPath(pub PathBuf);
+
+impl Default for NewickPath {
+    fn default() -> Self {
+        Self(PathBuf::from("phylogeny.nwk"))
+    }
+}
+
 #[derive(Resource)]
 pub struct ConsoleOutput {
     pub print_interval: f32,
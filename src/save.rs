@@ -0,0 +1,351 @@
+use bevy::prelude::*;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::components::*;
+use crate::config::{ConfigError, vec2_as_array};
+use crate::obstacles::Obstacles;
+use crate::phylogeny::BirthDeathLedger;
+use crate::resources::*;
+use crate::soil::SoilNutrients;
+use crate::species::Behavior;
+use crate::terrain::BiomeMap;
+
+// ===== SAVE FILES =====
+
+/// Bumped whenever a released save layout changes incompatibly
+pub const SAVE_VERSION: u32 = 1;
+
+/// A complete snapshot of a running world
+#[derive(Resource, Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SaveFile {
+    pub version: u32,
+    pub config: SimulationConfig,
+    /// Seed the simulation RNG continues from after loading
+    pub rng_seed: u64,
+    pub clock: SimulationClock,
+    pub lineage: LineageTracker,
+    pub ledger: BirthDeathLedger,
+    pub sunlight: SunlightLevel,
+    pub climate: Climate,
+    pub soil: SoilNutrients,
+    pub biomes: BiomeMap,
+    pub obstacles: Obstacles,
+    pub history: SimulationHistory,
+    pub organisms: Vec<SavedOrganism>,
+}
+
+/// One organism (or corpse). Entity references are stored as indices
+/// into `SaveFile::organisms`.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedOrganism {
+    /// Index into the saved config's species; corpses keep theirs in `corpse`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub species: Option<SpeciesId>,
+    pub translation: (f32, f32, f32),
+    pub genome: Genome,
+    /// Living organisms only; corpses are food through their biomass
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub energy: Option<f32>,
+    pub age: f32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lineage: Option<Lineage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub velocity: Option<(f32, f32)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub stamina: Option<Stamina>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hunt_target: Option<Option<usize>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub waypoint: Option<SavedWaypoint>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub corpse: Option<Corpse>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SavedWaypoint {
+    #[serde(with = "vec2_as_array")]
+    pub target: Vec2,
+    pub reached_threshold: f32,
+}
+
+/// Why a save file could not be written or read
+#[derive(Debug)]
+pub enum SaveError {
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    Serialize(ron::Error),
+    Parse {
+        path: PathBuf,
+        source: ron::error::SpannedError,
+    },
+    UnsupportedVersion(u32),
+    /// The saved config fails `SimulationConfig::validate`
+    InvalidConfig(ConfigError),
+    /// An organism (by index) belongs to a species the saved config lacks
+    UnknownSpecies {
+        organism: usize,
+        species: SpeciesId,
+    },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::Io { path, source } => {
+                write!(f, "could not access save '{}': {source}", path.display())
+            }
+            SaveError::Serialize(source) => write!(f, "could not serialize world: {source}"),
+            SaveError::Parse { path, source } => {
+                write!(f, "could not parse save '{}': {source}", path.display())
+            }
+            SaveError::UnsupportedVersion(version) => write!(
+                f,
+                "save version {version} is not supported (expected {SAVE_VERSION})"
+            ),
+            SaveError::InvalidConfig(source) => write!(f, "save holds an {source}"),
+            SaveError::UnknownSpecies { organism, species } => write!(
+                f,
+                "saved organism #{organism} has species id {} that the saved config does not define",
+                species.0
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+/// A save waiting to replace the current world; applied by `apply_pending_world_load`
+#[derive(Resource)]
+pub struct PendingWorldLoad(pub SaveFile);
+
+type SaveQuery<'w, 's> = (
+    Entity,
+    &'w Transform,
+    &'w Genome,
+    Option<&'w Energy>,
+    &'w Age,
+    Option<&'w Lineage>,
+    Option<&'w Velocity>,
+    Option<&'w Stamina>,
+    Option<&'w HuntTarget>,
+    Option<&'w ExplorationWaypoint>,
+    Option<&'w Corpse>,
+    Option<&'w SpeciesId>,
+);
+
+impl SaveFile {
+    /// Capture the current world. The simulation RNG is reseeded from itself
+    /// so the saved world and the running one continue identically.
+    pub fn capture(world: &mut World) -> Self {
+        let mut query = world.query::<SaveQuery>();
+        let rows: Vec<_> = query.iter(world).collect();
+
+        let index_of: std::collections::HashMap<Entity, usize> = rows
+            .iter()
+            .enumerate()
+            .map(|(index, row)| (row.0, index))
+            .collect();
+
+        let organisms = rows
+            .iter()
+            .map(
+                |&(
+                    _,
+                    transform,
+                    genome,
+                    energy,
+                    age,
+                    lineage,
+                    velocity,
+                    stamina,
+                    hunt_target,
+                    waypoint,
+                    corpse,
+                    species,
+                )| {
+                    let translation = transform.translation;
+
+                    SavedOrganism {
+                        species: species.copied(),
+                        translation: (translation.x, translation.y, translation.z),
+                        genome: genome.clone(),
+                        energy: energy.map(|e| e.0),
+                        age: age.0,
+                        lineage: lineage.cloned(),
+                        velocity: velocity.map(|v| (v.0.x, v.0.y)),
+                        stamina: stamina.cloned(),
+                        // Targets that are not saved (e.g. already despawned) are dropped
+                        hunt_target: hunt_target
+                            .map(|target| target.0.and_then(|e| index_of.get(&e).copied())),
+                        waypoint: waypoint.map(|w| SavedWaypoint {
+                            target: w.target,
+                            reached_threshold: w.reached_threshold,
+                        }),
+                        corpse: corpse.cloned(),
+                    }
+                },
+            )
+            .collect();
+
+        let rng_seed = world.resource_mut::<SimulationRng>().next_u64();
+        world.insert_resource(SimulationRng::new(rng_seed));
+
+        Self {
+            version: SAVE_VERSION,
+            config: world.resource::<SimulationConfig>().clone(),
+            rng_seed,
+            clock: world.resource::<SimulationClock>().clone(),
+            lineage: world.resource::<LineageTracker>().clone(),
+            ledger: world.resource::<BirthDeathLedger>().clone(),
+            sunlight: world.resource::<SunlightLevel>().clone(),
+            climate: world.resource::<Climate>().clone(),
+            soil: world.resource::<SoilNutrients>().clone(),
+            biomes: world.resource::<BiomeMap>().clone(),
+            obstacles: world.resource::<Obstacles>().clone(),
+            history: world.resource::<SimulationHistory>().clone(),
+            organisms,
+        }
+    }
+
+    /// Replace every organism and the simulation state with the saved ones
+    pub fn restore(&self, world: &mut World) {
+        // Resources first, so the ledger observers see the restored ledger
+        // while organisms are swapped out
+        world
+            .resource_mut::<Time<Fixed>>()
+            .set_timestep_hz(self.config.tick_rate);
+        world.insert_resource(self.config.clone());
+        world.insert_resource(SimulationRng::new(self.rng_seed));
+        world.insert_resource(self.clock.clone());
+        world.insert_resource(self.lineage.clone());
+        world.insert_resource(self.ledger.clone());
+        world.insert_resource(self.sunlight.clone());
+        world.insert_resource(self.climate.clone());
+        world.insert_resource(self.soil.clone());
+        world.insert_resource(self.biomes.clone());
+        world.insert_resource(self.obstacles.clone());
+        world.insert_resource(self.history.clone());
+
+        let existing: Vec<Entity> = world
+            .query_filtered::<Entity, With<Genome>>()
+            .iter(world)
+            .collect();
+        for entity in existing {
+            world.despawn(entity);
+        }
+
+        let entities: Vec<Entity> = self
+            .organisms
+            .iter()
+            .map(|saved| {
+                let (x, y, z) = saved.translation;
+                let mut entity = world.spawn((
+                    saved.genome.clone(),
+                    Age(saved.age),
+                    Transform::from_xyz(x, y, z),
+                ));
+                if let Some(energy) = saved.energy {
+                    entity.insert(Energy(energy));
+                }
+                if let Some(species) = saved.species {
+                    entity.insert(species);
+                    match self.config.species.get(species).behavior {
+                        Behavior::Producer => entity.insert(Producer),
+                        Behavior::Forager => entity.insert(Forager),
+                        Behavior::Hunter => entity.insert(Hunter),
+                        Behavior::Scavenger => entity.insert(Scavenger),
+                        Behavior::Omnivore => entity.insert(Omnivore),
+                    };
+                }
+                if let Some(lineage) = &saved.lineage {
+                    entity.insert(lineage.clone());
+                }
+                if let Some((vx, vy)) = saved.velocity {
+                    entity.insert(Velocity(Vec2::new(vx, vy)));
+                }
+                if let Some(stamina) = &saved.stamina {
+                    entity.insert(stamina.clone());
+                }
+                if let Some(waypoint) = &saved.waypoint {
+                    entity.insert(ExplorationWaypoint {
+                        target: waypoint.target,
+                        reached_threshold: waypoint.reached_threshold,
+                    });
+                }
+                if let Some(corpse) = &saved.corpse {
+                    entity.insert(corpse.clone());
+                }
+                entity.id()
+            })
+            .collect();
+
+        // Hunt targets refer to other organisms, so resolve them once all exist
+        for (saved, &entity) in self.organisms.iter().zip(&entities) {
+            if let Some(target) = saved.hunt_target {
+                let target = target.and_then(|index| entities.get(index).copied());
+                world.entity_mut(entity).insert(HuntTarget(target));
+            }
+        }
+    }
+
+    pub fn to_ron(&self) -> Result<String, SaveError> {
+        // One organism per line keeps large saves readable
+        let pretty = ron::ser::PrettyConfig::new().depth_limit(2);
+        ron::ser::to_string_pretty(self, pretty).map_err(SaveError::Serialize)
+    }
+
+    pub fn write(&self, path: impl AsRef<Path>) -> Result<(), SaveError> {
+        let path = path.as_ref();
+        std::fs::write(path, self.to_ron()?).map_err(|source| SaveError::Io {
+            path: path.to_path_buf(),
+            source,
+        })
+    }
+
+    pub fn read(path: impl AsRef<Path>) -> Result<Self, SaveError> {
+        let path = path.as_ref();
+        let text = std::fs::read_to_string(path).map_err(|source| SaveError::Io {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        let parse_error = |source| SaveError::Parse {
+            path: path.to_path_buf(),
+            source,
+        };
+
+        // Check the version first so old saves get a clear error instead of a parse failure
+        let header: SaveHeader = ron::from_str(&text).map_err(parse_error)?;
+        if header.version != SAVE_VERSION {
+            return Err(SaveError::UnsupportedVersion(header.version));
+        }
+        let save: Self = ron::from_str(&text).map_err(parse_error)?;
+        save.validate()?;
+        Ok(save)
+    }
+
+    /// Check a save can be restored: its config is valid and every organism
+    /// and corpse belongs to one of its species
+    pub fn validate(&self) -> Result<(), SaveError> {
+        self.config.validate().map_err(SaveError::InvalidConfig)?;
+        let species_count = self.config.species.len();
+        for (organism, saved) in self.organisms.iter().enumerate() {
+            let corpse = saved.corpse.as_ref().map(|corpse| corpse.species);
+            for species in saved.species.into_iter().chain(corpse) {
+                if species.0 >= species_count {
+                    return Err(SaveError::UnknownSpecies { organism, species });
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,70 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::resources::SoilConfig;
+use crate::utils::CellLayout;
+
+// ===== SOIL NUTRIENTS =====
+
+/// Nutrients in the ground, on a coarse grid over the torus world. Rotting
+/// corpses and animal waste put nutrients in; growing plants take them out.
+#[derive(Resource, Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct SoilNutrients {
+    layout: CellLayout,
+    cells: Vec<f32>,
+    /// Nutrients returned to the soil since the world was built
+    pub deposited: f32,
+    /// Nutrients plants have taken up since the world was built
+    pub absorbed: f32,
+}
+
+impl SoilNutrients {
+    /// Every cell starting at `config.initial_nutrients`
+    pub fn new(world_size: Vec2, config: &SoilConfig) -> Self {
+        let layout = CellLayout::new(world_size, config.cell_size);
+        Self {
+            layout,
+            cells: vec![config.initial_nutrients; layout.len()],
+            deposited: 0.0,
+            absorbed: 0.0,
+        }
+    }
+
+    /// Nutrients in the cell containing `position`
+    pub fn at(&self, position: Vec2) -> f32 {
+        self.index_of(position)
+            .map_or(0.0, |index| self.cells[index])
+    }
+
+    /// Return nutrients to the cell containing `position`
+    pub fn deposit(&mut self, position: Vec2, amount: f32) {
+        if amount <= 0.0 {
+            return;
+        }
+        if let Some(index) = self.index_of(position) {
+            self.cells[index] += amount;
+            self.deposited += amount;
+        }
+    }
+
+    /// Take up to `amount` from the cell containing `position`, returning
+    /// how much was there to take
+    pub fn take(&mut self, position: Vec2, amount: f32) -> f32 {
+        let Some(index) = self.index_of(position) else {
+            return 0.0;
+        };
+        let taken = amount.clamp(0.0, self.cells[index]);
+        self.cells[index] -= taken;
+        self.absorbed += taken;
+        taken
+    }
+
+    /// Nutrients in the whole world
+    pub fn total(&self) -> f32 {
+        self.cells.iter().sum()
+    }
+
+    fn index_of(&self, position: Vec2) -> Option<usize> {
+        (!self.layout.is_empty()).then(|| self.layout.index_of(position))
+    }
+}
@@ -0,0 +1,150 @@
+use bevy::prelude::*;
+
+use crate::components::SpeciesId;
+use crate::utils::wrapped_distance;
+
+// ===== SPATIAL HASH GRID =====
+
+/// What kind of entity a grid entry is; each kind has its own cells
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GridLayer {
+    Living(SpeciesId),
+    Corpse(SpeciesId),
+}
+
+impl GridLayer {
+    fn index(self) -> usize {
+        match self {
+            GridLayer::Living(species) => species.0 * 2,
+            GridLayer::Corpse(species) => species.0 * 2 + 1,
+        }
+    }
+}
+
+/// Uniform grid over the torus world, rebuilt every tick, so neighbour
+/// queries only look at nearby cells instead of every organism.
+///
+/// Cells are stretched slightly so a whole number of them spans the world,
+/// which keeps wrapping exact. Entries keep query order within a cell, so
+/// lookups are deterministic.
+#[derive(Resource, Default)]
+pub struct SpatialGrid {
+    world_size: Vec2,
+    cell_size: Vec2,
+    columns: usize,
+    rows: usize,
+    layers: Vec<Vec<Vec<(Entity, Vec2)>>>,
+}
+
+impl SpatialGrid {
+    /// Empty the grid, resizing it if the world, cell size or number of
+    /// species changed
+    pub fn reset(&mut self, world_size: Vec2, cell_size: f32, species_count: usize) {
+        let columns = ((world_size.x / cell_size) as usize).max(1);
+        let rows = ((world_size.y / cell_size) as usize).max(1);
+
+        if world_size != self.world_size
+            || columns != self.columns
+            || rows != self.rows
+            || self.layers.len() != species_count * 2
+        {
+            self.world_size = world_size;
+            self.columns = columns;
+            self.rows = rows;
+            self.cell_size = world_size / Vec2::new(columns as f32, rows as f32);
+            self.layers = vec![vec![Vec::new(); columns * rows]; species_count * 2];
+        } else {
+            for cell in self.layers.iter_mut().flatten() {
+                cell.clear();
+            }
+        }
+    }
+
+    pub fn insert(&mut self, layer: GridLayer, entity: Entity, position: Vec2) {
+        let (column, row) = self.cell_of(position);
+        let index = row * self.columns + column;
+        self.layers[layer.index()][index].push((entity, position));
+    }
+
+    /// Every entry of `layer` within `radius` of `position` (measured across
+    /// the world edges), with its distance
+    pub fn within(
+        &self,
+        layer: GridLayer,
+        position: Vec2,
+        radius: f32,
+    ) -> impl Iterator<Item = (Entity, Vec2, f32)> + '_ {
+        let cells = self
+            .layers
+            .get(layer.index())
+            .map_or(&[][..], Vec::as_slice);
+        let half = self.world_size / 2.0;
+        let min = ((position - radius + half) / self.cell_size).floor();
+        let max = ((position + radius + half) / self.cell_size).floor();
+        let (column_start, column_end) = axis_span(min.x as i64, max.x as i64, self.columns);
+        let (row_start, row_end) = axis_span(min.y as i64, max.y as i64, self.rows);
+        let (columns, rows) = (self.columns as i64, self.rows as i64);
+
+        (row_start..=row_end)
+            .flat_map(move |row| {
+                (column_start..=column_end).map(move |column| {
+                    (row.rem_euclid(rows) * columns + column.rem_euclid(columns)) as usize
+                })
+            })
+            .flat_map(move |index| cells.get(index).into_iter().flatten())
+            .filter_map(move |&(entity, other)| {
+                let distance = wrapped_distance(position, other, &self.world_size);
+                (distance <= radius).then_some((entity, other, distance))
+            })
+    }
+
+    /// `within` over several layers, one after another
+    pub fn within_any<'a>(
+        &'a self,
+        layers: &'a [GridLayer],
+        position: Vec2,
+        radius: f32,
+    ) -> impl Iterator<Item = (Entity, Vec2, f32)> + 'a {
+        layers
+            .iter()
+            .flat_map(move |&layer| self.within(layer, position, radius))
+    }
+
+    /// The closest entry of any of `layers` within `radius` that passes
+    /// `filter`. Ties go to the first entry found.
+    pub fn nearest(
+        &self,
+        layers: &[GridLayer],
+        position: Vec2,
+        radius: f32,
+        mut filter: impl FnMut(Entity) -> bool,
+    ) -> Option<(Entity, Vec2, f32)> {
+        self.within_any(layers, position, radius)
+            .filter(|&(entity, _, _)| filter(entity))
+            .fold(
+                None,
+                |best: Option<(Entity, Vec2, f32)>, candidate| match best {
+                    Some(best) if best.2 <= candidate.2 => Some(best),
+                    _ => Some(candidate),
+                },
+            )
+    }
+
+    fn cell_of(&self, position: Vec2) -> (usize, usize) {
+        let cell = ((position + self.world_size / 2.0) / self.cell_size).floor();
+        (
+            (cell.x as i64).rem_euclid(self.columns as i64) as usize,
+            (cell.y as i64).rem_euclid(self.rows as i64) as usize,
+        )
+    }
+}
+
+/// Cell indices to visit along one axis; a span covering the whole axis
+/// visits each cell once instead of wrapping onto itself
+fn axis_span(start: i64, end: i64, count: usize) -> (i64, i64) {
+    if end - start + 1 >= count as i64 {
+        (0, count as i64 - 1)
+    } else {
+        (start, end)
+    }
+}
@@ -0,0 +1,711 @@
+use bevy::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::components::*;
+
+// ===== SPECIES REGISTRY =====
+
+/// The built-in behavior a species runs; everything else about a species is data
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum Behavior {
+    /// Stationary, grows from sunlight
+    Producer,
+    /// Flocks, flees whatever eats it and grazes on its diet
+    Forager,
+    /// Claims and chases living targets from its diet
+    Hunter,
+    /// Explores in search of carrion
+    Scavenger,
+    /// Grazes when fed, chases small prey when hungry and flees whatever eats it
+    Omnivore,
+}
+
+/// Range each trait of a founder's genome is drawn from
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GenomeRanges {
+    pub speed: (f32, f32),
+    pub size: (f32, f32),
+    pub metabolism: (f32, f32),
+    pub reproduction_threshold: (f32, f32),
+    pub vision_range: (f32, f32),
+}
+
+impl GenomeRanges {
+    /// Each trait's range, by field name
+    pub fn traits(&self) -> [(&'static str, (f32, f32)); 5] {
+        [
+            ("speed", self.speed),
+            ("size", self.size),
+            ("metabolism", self.metabolism),
+            ("reproduction_threshold", self.reproduction_threshold),
+            ("vision_range", self.vision_range),
+        ]
+    }
+
+    pub fn sample(&self, rng: &mut impl Rng) -> Genome {
+        Genome {
+            speed: sample_range(self.speed, rng),
+            size: sample_range(self.size, rng),
+            metabolism: sample_range(self.metabolism, rng),
+            reproduction_threshold: sample_range(self.reproduction_threshold, rng),
+            vision_range: sample_range(self.vision_range, rng),
+        }
+    }
+}
+
+/// A value in `min..max`; an empty range (like a plant's speed) gives `min`
+pub fn sample_range((min, max): (f32, f32), rng: &mut impl Rng) -> f32 {
+    if min < max {
+        rng.random_range(min..max)
+    } else {
+        min
+    }
+}
+
+/// Founders appearing from nothing, scaled by sunlight
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Respawn {
+    /// Arrivals per second at full sunlight
+    pub rate: f32,
+    /// No arrivals at or above this population; never below `initial_count`
+    pub max_population: usize,
+}
+
+/// Founders arriving from outside while a species is rare
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Immigration {
+    /// Immigrants only arrive while the population is below this
+    pub threshold: usize,
+    /// Immigration events per second
+    pub rate: f32,
+    /// Smallest and largest group arriving in one event
+    pub group_size: (usize, usize),
+}
+
+/// Everything that defines one species
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct SpeciesConfig {
+    pub name: String,
+    pub behavior: Behavior,
+    pub initial_count: usize,
+    /// Energy of founders, drawn from `min..max`
+    pub initial_energy: (f32, f32),
+    pub genome: GenomeRanges,
+    /// Energy per second at full sunlight, per unit of size (producers only)
+    pub photosynthesis: f32,
+    /// Energy at which a producer stops growing (producers only)
+    pub max_energy: f32,
+    /// Biomass a corpse holds per unit of `Genome::size`
+    pub carcass_biomass: f32,
+    /// Reproduction events per second once above the genome's threshold
+    pub reproduction_rate: f32,
+    /// How far from its parent an offspring is placed
+    pub offspring_spread: f32,
+    pub respawn: Option<Respawn>,
+    pub immigration: Option<Immigration>,
+    /// Sees better in the dark rather than worse (animals only)
+    pub nocturnal: bool,
+    /// sRGB sprite color and base size, scaled by `Genome::size`
+    pub color: (f32, f32, f32),
+    pub sprite_size: f32,
+    pub corpse_color: (f32, f32, f32),
+    /// Draw order; higher is in front
+    pub layer: f32,
+}
+
+impl SpeciesConfig {
+    /// Biomass of a corpse of this species with `genome`
+    pub fn carcass(&self, genome: &Genome) -> f32 {
+        genome.size * self.carcass_biomass
+    }
+}
+
+impl Default for SpeciesConfig {
+    fn default() -> Self {
+        Self {
+            name: String::new(),
+            behavior: Behavior::Forager,
+            initial_count: 0,
+            initial_energy: (50.0, 50.0),
+            genome: GenomeRanges {
+                speed: (100.0, 100.0),
+                size: (1.0, 1.0),
+                metabolism: (1.0, 1.0),
+                reproduction_threshold: (100.0, 100.0),
+                vision_range: (100.0, 100.0),
+            },
+            photosynthesis: 0.0,
+            max_energy: 150.0,
+            carcass_biomass: 40.0,
+            reproduction_rate: 0.0,
+            offspring_spread: 20.0,
+            respawn: None,
+            immigration: None,
+            nocturnal: false,
+            color: (0.8, 0.8, 0.8),
+            sprite_size: 12.0,
+            corpse_color: (0.5, 0.5, 0.5),
+            layer: 1.0,
+        }
+    }
+}
+
+/// Every species in the world, in the order their ids are assigned
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SpeciesRegistry(pub Vec<SpeciesConfig>);
+
+impl Default for SpeciesRegistry {
+    fn default() -> Self {
+        let roaming = Immigration {
+            threshold: 5,
+            rate: 0.2,
+            group_size: (2, 3),
+        };
+
+        Self(vec![
+            SpeciesConfig {
+                name: "plant".into(),
+                behavior: Behavior::Producer,
+                initial_count: 600,
+                initial_energy: (20.0, 50.0),
+                genome: GenomeRanges {
+                    speed: (0.0, 0.0),
+                    size: (0.5, 1.5),
+                    metabolism: (0.3, 0.7),
+                    reproduction_threshold: (80.0, 120.0),
+                    vision_range: (0.0, 0.0),
+                },
+                photosynthesis: 0.5,
+                reproduction_rate: 0.6,
+                offspring_spread: 30.0,
+                respawn: Some(Respawn {
+                    rate: 2.0,
+                    max_population: 1200,
+                }),
+                color: (0.2, 0.8, 0.2),
+                sprite_size: 8.0,
+                corpse_color: (0.4, 0.4, 0.2),
+                layer: 0.0,
+                ..default()
+            },
+            SpeciesConfig {
+                name: "prey".into(),
+                behavior: Behavior::Forager,
+                initial_count: 160,
+                initial_energy: (40.0, 80.0),
+                genome: GenomeRanges {
+                    speed: (50.0, 150.0),
+                    size: (1.0, 2.0),
+                    metabolism: (0.25, 0.75),
+                    reproduction_threshold: (60.0, 100.0),
+                    vision_range: (80.0, 120.0),
+                },
+                reproduction_rate: 0.3,
+                immigration: Some(roaming.clone()),
+                color: (0.3, 0.3, 0.9),
+                sprite_size: 12.0,
+                corpse_color: (0.5, 0.5, 0.5),
+                layer: 1.0,
+                ..default()
+            },
+            SpeciesConfig {
+                name: "omnivore".into(),
+                behavior: Behavior::Omnivore,
+                initial_count: 40,
+                initial_energy: (50.0, 90.0),
+                genome: GenomeRanges {
+                    speed: (60.0, 130.0),
+                    size: (1.3, 2.2),
+                    metabolism: (0.8, 1.4),
+                    reproduction_threshold: (70.0, 120.0),
+                    vision_range: (90.0, 150.0),
+                },
+                reproduction_rate: 0.2,
+                immigration: Some(roaming.clone()),
+                color: (0.6, 0.3, 0.7),
+                sprite_size: 14.0,
+                corpse_color: (0.45, 0.35, 0.45),
+                layer: 1.2,
+                ..default()
+            },
+            SpeciesConfig {
+                name: "predator".into(),
+                behavior: Behavior::Hunter,
+                initial_count: 32,
+                initial_energy: (60.0, 100.0),
+                genome: GenomeRanges {
+                    speed: (80.0, 180.0),
+                    size: (1.5, 3.0),
+                    metabolism: (0.5, 1.0),
+                    reproduction_threshold: (80.0, 140.0),
+                    vision_range: (100.0, 180.0),
+                },
+                reproduction_rate: 0.18,
+                immigration: Some(roaming.clone()),
+                color: (0.9, 0.2, 0.2),
+                sprite_size: 16.0,
+                corpse_color: (0.6, 0.3, 0.3),
+                layer: 2.0,
+                ..default()
+            },
+            SpeciesConfig {
+                // Hunts predators, best at night; scarce, large and slow to breed
+                name: "apex".into(),
+                behavior: Behavior::Hunter,
+                initial_count: 6,
+                initial_energy: (100.0, 160.0),
+                genome: GenomeRanges {
+                    speed: (100.0, 190.0),
+                    size: (3.0, 4.5),
+                    metabolism: (0.3, 0.5),
+                    reproduction_threshold: (150.0, 220.0),
+                    vision_range: (150.0, 250.0),
+                },
+                carcass_biomass: 45.0,
+                reproduction_rate: 0.08,
+                immigration: Some(Immigration {
+                    threshold: 2,
+                    rate: 0.01,
+                    group_size: (1, 1),
+                }),
+                nocturnal: true,
+                color: (0.45, 0.05, 0.2),
+                sprite_size: 20.0,
+                corpse_color: (0.35, 0.2, 0.25),
+                layer: 2.5,
+                ..default()
+            },
+            SpeciesConfig {
+                name: "scavenger".into(),
+                behavior: Behavior::Scavenger,
+                initial_count: 48,
+                initial_energy: (50.0, 80.0),
+                genome: GenomeRanges {
+                    speed: (60.0, 120.0),
+                    size: (1.2, 2.5),
+                    metabolism: (0.3, 0.6),
+                    reproduction_threshold: (70.0, 110.0),
+                    vision_range: (120.0, 200.0),
+                },
+                carcass_biomass: 35.0,
+                reproduction_rate: 0.24,
+                immigration: Some(roaming),
+                color: (0.7, 0.5, 0.2),
+                sprite_size: 14.0,
+                corpse_color: (0.5, 0.4, 0.2),
+                layer: 1.5,
+                ..default()
+            },
+        ])
+    }
+}
+
+impl SpeciesRegistry {
+    pub fn get(&self, id: SpeciesId) -> &SpeciesConfig {
+        &self.0[id.0]
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (SpeciesId, &SpeciesConfig)> {
+        self.0
+            .iter()
+            .enumerate()
+            .map(|(index, species)| (SpeciesId(index), species))
+    }
+
+    pub fn id_of(&self, name: &str) -> Option<SpeciesId> {
+        self.0
+            .iter()
+            .position(|species| species.name == name)
+            .map(SpeciesId)
+    }
+
+    /// Same species with the same behaviors in the same order, so ids
+    /// and components of a running world stay valid
+    pub fn same_layout(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .0
+                .iter()
+                .zip(&other.0)
+                .all(|(a, b)| a.name == b.name && a.behavior == b.behavior)
+    }
+
+    /// Problems with the species definitions, for `SimulationConfig::validate`
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        for (index, species) in self.0.iter().enumerate() {
+            let name = &species.name;
+            if name.is_empty() {
+                problems.push(format!("species #{index} needs a name"));
+            } else if self.0[..index].iter().any(|other| &other.name == name) {
+                problems.push(format!("species '{name}' is defined more than once"));
+            }
+
+            let non_negative = [
+                ("photosynthesis", species.photosynthesis),
+                ("max_energy", species.max_energy),
+                ("carcass_biomass", species.carcass_biomass),
+                ("reproduction_rate", species.reproduction_rate),
+                ("offspring_spread", species.offspring_spread),
+                ("layer", species.layer),
+            ];
+            for (field, value) in non_negative {
+                if !(value.is_finite() && value >= 0.0) {
+                    problems.push(format!(
+                        "species '{name}' {field} must not be negative, got {value}"
+                    ));
+                }
+            }
+            if !(species.sprite_size.is_finite() && species.sprite_size > 0.0) {
+                problems.push(format!(
+                    "species '{name}' sprite_size must be positive, got {}",
+                    species.sprite_size
+                ));
+            }
+
+            let ranges = species
+                .genome
+                .traits()
+                .map(|(field, range)| (format!("genome.{field}"), range));
+            for (field, (min, max)) in
+                std::iter::once(("initial_energy".to_string(), species.initial_energy))
+                    .chain(ranges)
+            {
+                if !(min >= 0.0 && min <= max && max.is_finite()) {
+                    problems.push(format!(
+                        "species '{name}' {field} must satisfy 0 <= min <= max, got {min}..{max}"
+                    ));
+                }
+            }
+
+            if let Some(respawn) = &species.respawn {
+                if !(respawn.rate.is_finite() && respawn.rate >= 0.0) {
+                    problems.push(format!(
+                        "species '{name}' respawn.rate must not be negative, got {}",
+                        respawn.rate
+                    ));
+                }
+                if respawn.max_population < species.initial_count {
+                    problems.push(format!(
+                        "species '{name}' respawn.max_population ({}) must be at least initial_count ({})",
+                        respawn.max_population, species.initial_count
+                    ));
+                }
+            }
+            if let Some(immigration) = &species.immigration {
+                if !(immigration.rate.is_finite() && immigration.rate >= 0.0) {
+                    problems.push(format!(
+                        "species '{name}' immigration.rate must not be negative, got {}",
+                        immigration.rate
+                    ));
+                }
+                let (min, max) = immigration.group_size;
+                if min > max {
+                    problems.push(format!(
+                        "species '{name}' immigration.group_size must satisfy min <= max, got {min}..{max}"
+                    ));
+                }
+            }
+        }
+
+        problems
+    }
+}
+
+// ===== FOOD WEB =====
+
+/// How a consumer takes energy from a resource
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum FeedingMode {
+    /// Catches a living organism, then eats the carcass bite by bite
+    Kill,
+    /// Bites a living organism, which survives while it has energy left
+    Graze,
+    /// Bites a corpse
+    Scavenge,
+}
+
+/// One trophic relationship: `consumer` eats `resource`
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FoodLink {
+    pub consumer: String,
+    pub resource: String,
+    pub mode: FeedingMode,
+    /// Contact distance for eating
+    pub radius: f32,
+    /// Fraction of the energy taken that the consumer gains
+    pub efficiency: f32,
+    /// Energy (from the living) or biomass (from a corpse) taken per second
+    /// of feeding
+    pub bite: f32,
+    /// Resources holding no more than this are ignored
+    #[serde(default)]
+    pub min_energy: f32,
+    /// Only resources up to this multiple of the consumer's size are eaten
+    #[serde(default)]
+    pub max_relative_size: Option<f32>,
+}
+
+/// A `FoodLink` with its resource resolved against the registry
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct Feeding {
+    pub resource: SpeciesId,
+    pub mode: FeedingMode,
+    pub radius: f32,
+    pub efficiency: f32,
+    pub bite: f32,
+    pub min_energy: f32,
+    pub max_relative_size: Option<f32>,
+}
+
+impl Feeding {
+    /// Whether a consumer of `consumer_size` can take a resource of `resource_size`
+    pub fn fits(&self, consumer_size: f32, resource_size: f32) -> bool {
+        self.max_relative_size
+            .is_none_or(|ratio| resource_size <= ratio * consumer_size)
+    }
+}
+
+/// Who eats whom. A consumer tries its links in table order and eats at
+/// most once per tick
+#[derive(Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct FoodWeb(pub Vec<FoodLink>);
+
+impl Default for FoodWeb {
+    fn default() -> Self {
+        use FeedingMode::*;
+
+        let link = |consumer: &str, resource: &str, mode, radius, efficiency, bite| FoodLink {
+            consumer: consumer.into(),
+            resource: resource.into(),
+            mode,
+            radius,
+            efficiency,
+            bite,
+            // Corpses must hold some energy to be worth eating
+            min_energy: if mode == Scavenge { 10.0 } else { 0.0 },
+            max_relative_size: None,
+        };
+
+        Self(vec![
+            FoodLink {
+                // Plants must regrow a little before they are worth grazing
+                min_energy: 20.0,
+                ..link("prey", "plant", Graze, 15.0, 1.0, 1800.0)
+            },
+            FoodLink {
+                min_energy: 20.0,
+                ..link("omnivore", "plant", Graze, 15.0, 0.8, 1500.0)
+            },
+            FoodLink {
+                // Only prey smaller than itself
+                max_relative_size: Some(1.0),
+                ..link("omnivore", "prey", Kill, 18.0, 0.6, 360.0)
+            },
+            link("predator", "prey", Kill, 20.0, 0.7, 480.0),
+            link("predator", "omnivore", Kill, 20.0, 0.7, 480.0),
+            link("predator", "prey", Scavenge, 20.0, 0.6, 480.0),
+            link("predator", "omnivore", Scavenge, 20.0, 0.6, 480.0),
+            link("predator", "predator", Scavenge, 20.0, 0.6, 480.0),
+            // Add a Kill link to "scavenger" to let apex predators hunt them too
+            link("apex", "predator", Kill, 25.0, 0.7, 600.0),
+            link("apex", "predator", Scavenge, 25.0, 0.6, 600.0),
+            link("scavenger", "prey", Scavenge, 15.0, 0.8, 360.0),
+            link("scavenger", "omnivore", Scavenge, 15.0, 0.8, 360.0),
+            link("scavenger", "predator", Scavenge, 15.0, 0.8, 360.0),
+            link("scavenger", "apex", Scavenge, 15.0, 0.8, 360.0),
+        ])
+    }
+}
+
+impl FoodWeb {
+    /// Everything `consumer` eats, in table order; links naming unknown
+    /// species are skipped
+    pub fn feedings(&self, registry: &SpeciesRegistry, consumer: SpeciesId) -> Vec<Feeding> {
+        let name = &registry.get(consumer).name;
+        self.0
+            .iter()
+            .filter(|link| &link.consumer == name)
+            .filter_map(|link| {
+                Some(Feeding {
+                    resource: registry.id_of(&link.resource)?,
+                    mode: link.mode,
+                    radius: link.radius,
+                    efficiency: link.efficiency,
+                    bite: link.bite,
+                    min_energy: link.min_energy,
+                    max_relative_size: link.max_relative_size,
+                })
+            })
+            .collect()
+    }
+
+    /// Living species `consumer` kills or grazes
+    pub fn diet(&self, registry: &SpeciesRegistry, consumer: SpeciesId) -> Vec<SpeciesId> {
+        let mut diet = Vec::new();
+        for feeding in self.feedings(registry, consumer) {
+            if feeding.mode != FeedingMode::Scavenge && !diet.contains(&feeding.resource) {
+                diet.push(feeding.resource);
+            }
+        }
+        diet
+    }
+
+    /// Species whose corpses `consumer` eats
+    pub fn carrion(&self, registry: &SpeciesRegistry, consumer: SpeciesId) -> Vec<SpeciesId> {
+        let mut carrion = Vec::new();
+        for feeding in self.feedings(registry, consumer) {
+            if feeding.mode == FeedingMode::Scavenge && !carrion.contains(&feeding.resource) {
+                carrion.push(feeding.resource);
+            }
+        }
+        carrion
+    }
+
+    /// Species that kill `resource`
+    pub fn threats(&self, registry: &SpeciesRegistry, resource: SpeciesId) -> Vec<SpeciesId> {
+        registry
+            .iter()
+            .filter(|&(consumer, _)| {
+                self.feedings(registry, consumer).iter().any(|feeding| {
+                    feeding.resource == resource && feeding.mode == FeedingMode::Kill
+                })
+            })
+            .map(|(consumer, _)| consumer)
+            .collect()
+    }
+
+    /// Problems with the links, for `SimulationConfig::validate`
+    pub fn problems(&self, registry: &SpeciesRegistry) -> Vec<String> {
+        let mut problems = Vec::new();
+
+        for link in &self.0 {
+            let label = format!("food_web link '{}' -> '{}'", link.consumer, link.resource);
+            for name in [&link.consumer, &link.resource] {
+                if registry.id_of(name).is_none() {
+                    problems.push(format!("{label} names unknown species '{name}'"));
+                }
+            }
+            if let Some(consumer) = registry.id_of(&link.consumer)
+                && registry.get(consumer).behavior == Behavior::Producer
+            {
+                problems.push(format!("{label}: producers cannot eat"));
+            }
+            let non_negative = [
+                ("radius", link.radius),
+                ("efficiency", link.efficiency),
+                ("min_energy", link.min_energy),
+                ("max_relative_size", link.max_relative_size.unwrap_or(0.0)),
+            ];
+            for (field, value) in non_negative {
+                if !(value.is_finite() && value >= 0.0) {
+                    problems.push(format!("{label} {field} must not be negative, got {value}"));
+                }
+            }
+            if !(link.bite.is_finite() && link.bite > 0.0) {
+                problems.push(format!("{label} bite must be positive, got {}", link.bite));
+            }
+        }
+
+        problems
+    }
+}
+
+// ===== SPAWNING =====
+
+/// Spawn one organism of `species` with the components its behavior needs
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_organism(
+    commands: &mut Commands,
+    registry: &SpeciesRegistry,
+    species: SpeciesId,
+    position: Vec2,
+    genome: Genome,
+    energy: f32,
+    lineage: Lineage,
+    rng: &mut impl Rng,
+) -> Entity {
+    let config = registry.get(species);
+    let mut entity = commands.spawn((
+        species,
+        genome,
+        Energy(energy),
+        Age(0.0),
+        lineage,
+        Transform::from_xyz(position.x, position.y, config.layer),
+    ));
+    match config.behavior {
+        Behavior::Producer => entity.insert(Producer),
+        Behavior::Forager => entity.insert((Forager, Velocity(Vec2::ZERO), Stamina::default())),
+        Behavior::Hunter => entity.insert((
+            Hunter,
+            Velocity(Vec2::ZERO),
+            HuntTarget(None),
+            initial_waypoint(position, rng),
+        )),
+        Behavior::Scavenger => entity.insert((
+            Scavenger,
+            Velocity(Vec2::ZERO),
+            initial_waypoint(position, rng),
+        )),
+        Behavior::Omnivore => entity.insert((
+            Omnivore,
+            Velocity(Vec2::ZERO),
+            HuntTarget(None),
+            initial_waypoint(position, rng),
+        )),
+    };
+    entity.id()
+}
+
+/// Spawn a founder of `species` with a genome and energy drawn from its ranges
+pub fn spawn_founder(
+    commands: &mut Commands,
+    registry: &SpeciesRegistry,
+    species: SpeciesId,
+    position: Vec2,
+    lineage: Lineage,
+    rng: &mut impl Rng,
+) -> Entity {
+    let config = registry.get(species);
+    let genome = config.genome.sample(rng);
+    let energy = sample_range(config.initial_energy, rng);
+    spawn_organism(
+        commands, registry, species, position, genome, energy, lineage, rng,
+    )
+}
+
+/// First exploration waypoint for a newly spawned wanderer
+fn initial_waypoint(position: Vec2, rng: &mut impl Rng) -> ExplorationWaypoint {
+    let waypoint_angle = rng.random_range(0.0..std::f32::consts::TAU);
+    let waypoint_distance = rng.random_range(100.0..200.0);
+    ExplorationWaypoint {
+        target: position + Vec2::from_angle(waypoint_angle) * waypoint_distance,
+        reached_threshold: 30.0,
+    }
+}
+
+/// A uniformly random point in the world
+pub fn random_position(world_size: Vec2, rng: &mut impl Rng) -> Vec2 {
+    Vec2::new(
+        rng.random_range(-world_size.x / 2.0..world_size.x / 2.0),
+        rng.random_range(-world_size.y / 2.0..world_size.y / 2.0),
+    )
+}
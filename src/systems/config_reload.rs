@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+
+use crate::resources::*;
+
+// ===== CONFIG HOT RELOAD =====
+
+pub fn config_hot_reload_system(
+    mut watcher: ResMut<ConfigWatcher>,
+    mut config: ResMut<SimulationConfig>,
+    time: Res<Time>,
+) {
+    watcher.time_since_poll += time.delta_secs();
+    if watcher.time_since_poll < watcher.poll_interval {
+        return;
+    }
+    watcher.time_since_poll = 0.0;
+
+    let Ok(contents) = std::fs::read_to_string(&watcher.path) else {
+        return;
+    };
+    if watcher.last_contents.as_ref() == Some(&contents) {
+        return;
+    }
+    watcher.last_contents = Some(contents.clone());
+
+    let path = watcher.path.display();
+    let new_config = match SimulationConfig::from_ron(&contents) {
+        Ok(new_config) => new_config,
+        Err(err) => {
+            warn!("Ignoring edit to {path}: {err}");
+            return;
+        }
+    };
+    if let Err(err) = new_config.validate() {
+        warn!("Ignoring edit to {path}: {err}");
+        return;
+    }
+
+    let changes = config.changed_fields(&new_config);
+    let (restart_only, live): (Vec<_>, Vec<_>) = changes
+        .into_iter()
+        .partition(|change| change.requires_restart());
+
+    for change in &restart_only {
+        warn!(
+            "{} changed in {path} ({} -> {}) but only takes effect after a restart",
+            change.field, change.old, change.new
+        );
+    }
+    if live.is_empty() {
+        return;
+    }
+
+    for change in &live {
+        info!(
+            "Config reloaded: {} {} -> {}",
+            change.field, change.old, change.new
+        );
+    }
+    config.apply_live(&new_config);
+}
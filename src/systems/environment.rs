@@ -1,185 +1,200 @@
 use bevy::prelude::*;
-use rand::Rng;
+use rand::{Rng, RngCore};
 
 use crate::components::*;
+use crate::obstacles::Obstacles;
 use crate::resources::*;
+use crate::soil::SoilNutrients;
+use crate::species::spawn_founder;
+use crate::terrain::BiomeMap;
+use crate::utils::chance_per_tick;
 
 // ===== ENVIRONMENT SYSTEMS =====
 
-pub fn sunlight_cycle_system(mut sunlight: ResMut<SunlightLevel>, time: Res<Time>) {
-    sunlight.cycle_time += time.delta_secs();
-    sunlight.intensity = (sunlight.cycle_time * 0.5).sin() * 0.3 + 0.7;
+pub fn simulation_clock_system(mut clock: ResMut<SimulationClock>, time: Res<Time>) {
+    clock.elapsed += time.delta_secs();
 }
 
+pub fn draw_tick_seed_system(mut tick_seed: ResMut<TickSeed>, mut rng: ResMut<SimulationRng>) {
+    tick_seed.0 = rng.next_u64();
+}
+
+/// Bring the climate up to the simulation clock, drawing each new year's
+/// weather as it begins, and set the sunlight from it
+pub fn climate_system(
+    mut climate: ResMut<Climate>,
+    mut sunlight: ResMut<SunlightLevel>,
+    clock: Res<SimulationClock>,
+    config: Res<SimulationConfig>,
+    mut rng: ResMut<SimulationRng>,
+) {
+    let climate_config = &config.climate;
+    let year = (clock.elapsed / climate_config.year_length) as u32;
+    if year != climate.year {
+        climate.year = year;
+        let roll: f32 = rng.random();
+        climate.year_kind = if roll < climate_config.drought_chance {
+            YearKind::Drought
+        } else if roll < climate_config.drought_chance + climate_config.wet_chance {
+            YearKind::Wet
+        } else {
+            YearKind::Normal
+        };
+    }
+    climate.update(clock.elapsed, climate_config);
+    sunlight.intensity = climate.sunlight(climate_config);
+}
+
+/// Plants grow from sunlight up to their species' `max_energy`, as fast as the
+/// weather, their biome and the nutrients in their soil cell allow, and take up
+/// nutrients for what they grow
+#[allow(clippy::too_many_arguments)]
 pub fn plant_growth_system(
-    mut plants: Query<(&mut Energy, &Genome), With<Plant>>,
+    mut producers: Query<(&mut Energy, &Transform, &Genome, &SpeciesId), With<Producer>>,
+    mut soil: ResMut<SoilNutrients>,
+    biomes: Res<BiomeMap>,
     sunlight: Res<SunlightLevel>,
+    climate: Res<Climate>,
     config: Res<SimulationConfig>,
     time: Res<Time>,
 ) {
-    for (mut energy, genome) in plants.iter_mut() {
-        let growth =
-            config.plant_energy_from_sun * sunlight.intensity * genome.size * time.delta_secs();
-        energy.0 = (energy.0 + growth).min(150.0);
+    let uptake = config.soil.uptake;
+    for (mut energy, transform, genome, &species) in producers.iter_mut() {
+        let position = transform.translation.truncate();
+        let species = config.species.get(species);
+        let fertility = config.soil.fertility(soil.at(position))
+            * config.terrain.biome(biomes.at(position)).growth;
+        let mut growth = (species.photosynthesis * sunlight.intensity * climate.growth)
+            * genome.size
+            * fertility
+            * time.delta_secs();
+        growth = growth.min(species.max_energy - energy.0).max(0.0);
+        if uptake > 0.0 {
+            growth = soil.take(position, growth * uptake) / uptake;
+        }
+        energy.0 += growth;
+    }
+}
+
+/// Living organisms of each species, indexed by `SpeciesId`
+fn species_counts(organisms: &Query<&SpeciesId>, species_count: usize) -> Vec<usize> {
+    let mut counts = vec![0; species_count];
+    for species in organisms.iter() {
+        if let Some(count) = counts.get_mut(species.0) {
+            *count += 1;
+        }
     }
+    counts
 }
 
+/// Random spots tried per arrival; the first fertile enough one is used
+const RESPAWN_ATTEMPTS: usize = 8;
+
+/// A random open spot, each try kept with a chance set by its soil's fertility
+/// and its biome
+fn fertile_site(
+    config: &SimulationConfig,
+    soil: &SoilNutrients,
+    biomes: &BiomeMap,
+    obstacles: &Obstacles,
+    rng: &mut impl Rng,
+) -> Option<Vec2> {
+    for _ in 0..RESPAWN_ATTEMPTS {
+        let position = obstacles.random_open_position(rng);
+        let fertility = config.soil.fertility(soil.at(position))
+            * config.terrain.biome(biomes.at(position)).respawn;
+        if rng.random_bool(fertility.clamp(0.0, 1.0) as f64) {
+            return Some(position);
+        }
+    }
+    None
+}
+
+/// Founders of species with a `respawn` rule appear at random while below
+/// their maximum population; more arrive in sunny, growing weather, and they
+/// take root more readily where the soil is rich and the biome suits them
+#[allow(clippy::too_many_arguments)]
 pub fn plant_respawn_system(
     mut commands: Commands,
-    plants: Query<&Plant>,
+    organisms: Query<&SpeciesId>,
+    soil: Res<SoilNutrients>,
+    biomes: Res<BiomeMap>,
+    obstacles: Res<Obstacles>,
     sunlight: Res<SunlightLevel>,
+    climate: Res<Climate>,
     config: Res<SimulationConfig>,
+    mut rng: ResMut<SimulationRng>,
+    mut lineage: ResMut<LineageTracker>,
+    clock: Res<SimulationClock>,
     time: Res<Time>,
 ) {
-    let current_plant_count = plants.iter().count();
-
-    // Only spawn new plants if below the maximum
-    if current_plant_count < config.max_plants {
-        // Probability of spawning a new plant is based on sunlight intensity
-        // Higher sunlight = more plant spawns
-        let spawn_chance = config.plant_respawn_rate * sunlight.intensity * time.delta_secs();
-
-        let mut rng = rand::rng();
-        if rng.random_bool(spawn_chance as f64) {
-            // Spawn a new plant at a random location
-            let x = rng.random_range(-config.world_size.x / 2.0..config.world_size.x / 2.0);
-            let y = rng.random_range(-config.world_size.y / 2.0..config.world_size.y / 2.0);
+    let counts = species_counts(&organisms, config.species.len());
+
+    for (species, species_config) in config.species.iter() {
+        let Some(respawn) = &species_config.respawn else {
+            continue;
+        };
+        if counts[species.0] >= respawn.max_population {
+            continue;
+        }
 
-            commands.spawn((
-                Plant,
-                Genome::random_plant(),
-                Energy(rng.random_range(20.0..40.0)),
-                Age(0.0),
-                Transform::from_xyz(x, y, 0.0),
-                Sprite {
-                    color: Color::srgb(0.2, 0.8, 0.2),
-                    custom_size: Some(Vec2::splat(8.0)),
-                    ..default()
-                },
-            ));
+        let spawn_chance = chance_per_tick(
+            respawn.rate * sunlight.intensity * climate.growth,
+            time.delta_secs(),
+        );
+        if !rng.random_bool(spawn_chance) {
+            continue;
+        }
+        if let Some(position) = fertile_site(&config, &soil, &biomes, &obstacles, &mut *rng) {
+            spawn_founder(
+                &mut commands,
+                &config.species,
+                species,
+                position,
+                lineage.founder(clock.elapsed),
+                &mut *rng,
+            );
         }
     }
 }
 
+/// Small groups of founders arrive from outside while a species with an
+/// `immigration` rule is below its threshold
+#[allow(clippy::too_many_arguments)]
 pub fn immigration_system(
     mut commands: Commands,
-    prey: Query<&Prey>,
-    predators: Query<&Predator>,
-    scavengers: Query<&Scavenger>,
+    organisms: Query<&SpeciesId>,
+    obstacles: Res<Obstacles>,
     config: Res<SimulationConfig>,
+    mut rng: ResMut<SimulationRng>,
+    mut lineage: ResMut<LineageTracker>,
+    clock: Res<SimulationClock>,
     time: Res<Time>,
 ) {
-    let mut rng = rand::rng();
-
-    // Prey immigration - when population drops below 5
-    let prey_count = prey.iter().count();
-    if prey_count < 5 {
-        // 2% chance per second of immigration event
-        let immigration_chance = 0.02 * time.delta_secs();
-        if rng.random_bool(immigration_chance as f64) {
-            // Spawn 1-2 immigrant prey
-            let immigrant_count = rng.random_range(1..=2);
-            for _ in 0..immigrant_count {
-                let x = rng.random_range(-config.world_size.x / 2.0..config.world_size.x / 2.0);
-                let y = rng.random_range(-config.world_size.y / 2.0..config.world_size.y / 2.0);
-
-                commands.spawn((
-                    Prey,
-                    Genome::random_prey(),
-                    Energy(rng.random_range(40.0..80.0)),
-                    Age(0.0),
-                    Velocity(Vec2::ZERO),
-                    Stamina::default(),
-                    Transform::from_xyz(x, y, 1.0),
-                    Sprite {
-                        color: Color::srgb(0.3, 0.3, 0.9),
-                        custom_size: Some(Vec2::splat(12.0)),
-                        ..default()
-                    },
-                ));
-            }
+    let counts = species_counts(&organisms, config.species.len());
+
+    for (species, species_config) in config.species.iter() {
+        let Some(immigration) = &species_config.immigration else {
+            continue;
+        };
+        if counts[species.0] >= immigration.threshold {
+            continue;
         }
-    }
 
-    // Predator immigration - when population drops below 5
-    let predator_count = predators.iter().count();
-    if predator_count < 5 {
-        // 2% chance per second of immigration event
-        let immigration_chance = 0.02 * time.delta_secs();
-        if rng.random_bool(immigration_chance as f64) {
-            // Spawn 1-2 immigrant predators
-            let immigrant_count = rng.random_range(1..=2);
+        // Chance per second of an immigration event
+        if rng.random_bool(chance_per_tick(immigration.rate, time.delta_secs())) {
+            let (min, max) = immigration.group_size;
+            let immigrant_count = rng.random_range(min..=max);
             for _ in 0..immigrant_count {
-                let x = rng.random_range(-config.world_size.x / 2.0..config.world_size.x / 2.0);
-                let y = rng.random_range(-config.world_size.y / 2.0..config.world_size.y / 2.0);
-
-                // Generate initial exploration waypoint
-                let waypoint_angle = rng.random_range(0.0..std::f32::consts::TAU);
-                let waypoint_distance = rng.random_range(100.0..200.0);
-                let waypoint_target = Vec2::new(
-                    x + waypoint_angle.cos() * waypoint_distance,
-                    y + waypoint_angle.sin() * waypoint_distance,
+                let position = obstacles.random_open_position(&mut *rng);
+                spawn_founder(
+                    &mut commands,
+                    &config.species,
+                    species,
+                    position,
+                    lineage.founder(clock.elapsed),
+                    &mut *rng,
                 );
-
-                commands.spawn((
-                    Predator,
-                    Genome::random_predator(),
-                    Energy(rng.random_range(60.0..100.0)),
-                    Age(0.0),
-                    Velocity(Vec2::ZERO),
-                    HuntTarget(None),
-                    ExplorationWaypoint {
-                        target: waypoint_target,
-                        reached_threshold: 30.0,
-                    },
-                    Transform::from_xyz(x, y, 2.0),
-                    Sprite {
-                        color: Color::srgb(0.9, 0.2, 0.2),
-                        custom_size: Some(Vec2::splat(16.0)),
-                        ..default()
-                    },
-                ));
-            }
-        }
-    }
-
-    // Scavenger immigration - when population drops below 5
-    let scavenger_count = scavengers.iter().count();
-    if scavenger_count < 5 {
-        // 2% chance per second of immigration event
-        let immigration_chance = 0.02 * time.delta_secs();
-        if rng.random_bool(immigration_chance as f64) {
-            // Spawn 1-2 immigrant scavengers
-            let immigrant_count = rng.random_range(1..=2);
-            for _ in 0..immigrant_count {
-                let x = rng.random_range(-config.world_size.x / 2.0..config.world_size.x / 2.0);
-                let y = rng.random_range(-config.world_size.y / 2.0..config.world_size.y / 2.0);
-
-                // Generate initial exploration waypoint
-                let waypoint_angle = rng.random_range(0.0..std::f32::consts::TAU);
-                let waypoint_distance = rng.random_range(100.0..200.0);
-                let waypoint_target = Vec2::new(
-                    x + waypoint_angle.cos() * waypoint_distance,
-                    y + waypoint_angle.sin() * waypoint_distance,
-                );
-
-                commands.spawn((
-                    Scavenger,
-                    Genome::random_scavenger(),
-                    Energy(rng.random_range(50.0..90.0)),
-                    Age(0.0),
-                    Velocity(Vec2::ZERO),
-                    ExplorationWaypoint {
-                        target: waypoint_target,
-                        reached_threshold: 30.0,
-                    },
-                    Transform::from_xyz(x, y, 1.5),
-                    Sprite {
-                        color: Color::srgb(0.7, 0.5, 0.2),
-                        custom_size: Some(Vec2::splat(14.0)),
-                        ..default()
-                    },
-                ));
             }
         }
     }
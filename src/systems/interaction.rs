@@ -1,148 +1,180 @@
 use bevy::prelude::*;
+use std::collections::HashSet;
 
 use crate::components::*;
 use crate::resources::*;
+use crate::spatial::{GridLayer, SpatialGrid};
+use crate::species::{Feeding, FeedingMode};
 
-// ===== QUERY TYPE ALIASES =====
-
-type EatingPreyQuery<'w, 's> = Query<
-    'w,
-    's,
-    (&'static Transform, &'static mut Energy, &'static Genome),
-    (
-        With<Prey>,
-        Without<Plant>,
-        Without<Predator>,
-        Without<Scavenger>,
-    ),
->;
-
-type EatingPredatorQuery<'w, 's> = Query<
-    'w,
-    's,
-    (&'static Transform, &'static mut Energy, &'static Genome),
-    (
-        With<Predator>,
-        Without<Prey>,
-        Without<Plant>,
-        Without<Scavenger>,
-    ),
->;
+use super::lifecycle::make_corpse;
 
-type EatingPlantQuery<'w, 's> = Query<
-    'w,
-    's,
-    (Entity, &'static Transform, &'static Energy),
-    (
-        With<Plant>,
-        Without<Prey>,
-        Without<Predator>,
-        Without<Scavenger>,
-    ),
->;
+// ===== QUERY TYPE ALIASES =====
 
-type ScavengerEatingQuery<'w, 's> = Query<
+type EaterQuery<'w, 's> = Query<
     'w,
     's,
-    (&'static Transform, &'static mut Energy),
     (
-        With<Scavenger>,
-        Without<Corpse>,
-        Without<Prey>,
-        Without<Predator>,
-        Without<Plant>,
+        Entity,
+        &'static Transform,
+        &'static Genome,
+        &'static SpeciesId,
+        Option<&'static mut HuntTarget>,
     ),
+    Without<Producer>,
 >;
 
-type CorpseQuery<'w, 's> = Query<
-    'w,
-    's,
-    (Entity, &'static Transform, &'static Energy),
-    (
-        With<Corpse>,
-        Without<Predator>,
-        Without<Prey>,
-        Without<Scavenger>,
-        Without<Plant>,
-    ),
->;
+/// What an animal found to eat this tick
+enum Meal {
+    /// A bite out of something alive
+    Graze(Entity),
+    /// A catch, which becomes a carcass
+    Kill(Entity),
+    /// A bite out of a corpse
+    Carcass(Entity),
+}
 
 // ===== INTERACTION SYSTEMS =====
 
+/// Every animal eats at most once per tick: from the first of its food-web
+/// links with something edible within the link's contact radius. Hunters
+/// finish their catch, whichever kill link took it, before catching something
+/// new. A kill leaves a carcass
+/// that only its killer eats through its kill link; everyone else needs a
+/// scavenge link.
 #[allow(clippy::too_many_arguments)]
 pub fn eating_system(
     mut commands: Commands,
-    mut prey: EatingPreyQuery,
-    mut predators: EatingPredatorQuery,
-    mut scavengers: ScavengerEatingQuery,
-    plants: EatingPlantQuery,
-    prey_entities: Query<Entity, With<Prey>>,
-    corpses: CorpseQuery,
+    mut eaters: EaterQuery,
+    mut energies: Query<&mut Energy>,
+    mut corpses: Query<&mut Corpse>,
+    genomes: Query<&Genome>,
+    grid: Res<SpatialGrid>,
     config: Res<SimulationConfig>,
+    time: Res<Time>,
 ) {
-    // Prey eating plants
-    for (prey_transform, mut prey_energy, _genome) in prey.iter_mut() {
-        for (plant_entity, plant_transform, plant_energy) in plants.iter() {
-            let distance = prey_transform
-                .translation
-                .distance(plant_transform.translation);
-            if distance < 15.0 && plant_energy.0 > 20.0 {
-                prey_energy.0 += config.prey_energy_from_plant;
-                commands.entity(plant_entity).despawn();
-                break;
-            }
+    let registry = &config.species;
+    let dt = time.delta_secs();
+    let feedings: Vec<Vec<Feeding>> = registry
+        .iter()
+        .map(|(species, _)| config.food_web.feedings(registry, species))
+        .collect();
+
+    // Only used for lookups, never iterated
+    let mut eaten = HashSet::new();
+
+    for (eater, transform, genome, &species, mut claim) in eaters.iter_mut() {
+        if eaten.contains(&eater) {
+            continue;
         }
-    }
+        let position = transform.translation.xy();
+        let catch = claim.as_ref().and_then(|claim| claim.0);
+
+        let eaten_so_far = &eaten;
+        let nearby = |layer, radius: f32| {
+            grid.within(layer, position, radius)
+                .filter(move |&(food, _, distance)| {
+                    distance < radius && food != eater && !eaten_so_far.contains(&food)
+                })
+                .map(|(food, _, _)| food)
+        };
 
-    // Predators eating prey (living)
-    for (predator_transform, mut predator_energy, _genome) in predators.iter_mut() {
-        let mut ate_something = false;
+        // A catch still on the ground is finished first, through whichever
+        // kill link took it
+        let leftovers = catch.and_then(|catch| {
+            let corpse = corpses
+                .get(catch)
+                .ok()
+                .filter(|corpse| corpse.biomass > 0.0)?;
+            feedings[species.0]
+                .iter()
+                .find(|feeding| {
+                    feeding.mode == FeedingMode::Kill
+                        && feeding.resource == corpse.species
+                        && nearby(GridLayer::Corpse(corpse.species), feeding.radius)
+                            .any(|food| food == catch)
+                })
+                .map(|feeding| (Meal::Carcass(catch), feeding))
+        });
 
-        // First try to eat living prey
-        for prey_entity in prey_entities.iter() {
-            if let Ok((prey_transform, _, _)) = prey.get(prey_entity) {
-                let distance = predator_transform
-                    .translation
-                    .distance(prey_transform.translation);
-                if distance < 20.0 {
-                    predator_energy.0 += config.predator_energy_from_prey;
-                    commands.entity(prey_entity).despawn();
-                    ate_something = true;
-                    break;
+        let meal = leftovers.or_else(|| {
+            feedings[species.0].iter().find_map(|feeding| {
+                let living = || {
+                    nearby(GridLayer::Living(feeding.resource), feeding.radius).find(|&food| {
+                        energies
+                            .get(food)
+                            .is_ok_and(|energy| energy.0 > feeding.min_energy)
+                            && genomes
+                                .get(food)
+                                .is_ok_and(|food| feeding.fits(genome.size, food.size))
+                    })
+                };
+                let meal = match feeding.mode {
+                    FeedingMode::Graze => living().map(Meal::Graze),
+                    FeedingMode::Kill => living().map(Meal::Kill),
+                    FeedingMode::Scavenge => {
+                        nearby(GridLayer::Corpse(feeding.resource), feeding.radius)
+                            .find(|&food| {
+                                corpses
+                                    .get(food)
+                                    .is_ok_and(|corpse| corpse.biomass > feeding.min_energy)
+                            })
+                            .map(Meal::Carcass)
+                    }
+                };
+                meal.map(|meal| (meal, feeding))
+            })
+        });
+
+        let Some((meal, feeding)) = meal else {
+            continue;
+        };
+
+        let taken = match meal {
+            Meal::Graze(food) => {
+                let Ok(mut food_energy) = energies.get_mut(food) else {
+                    continue;
+                };
+                let taken = (feeding.bite * dt).min(food_energy.0);
+                food_energy.0 -= taken;
+                if food_energy.0 <= 0.0 {
+                    eaten.insert(food);
+                    commands.entity(food).despawn();
                 }
+                taken
             }
-        }
-
-        // If no living prey found, try to scavenge corpses
-        if !ate_something {
-            for (corpse_entity, corpse_transform, corpse_energy) in corpses.iter() {
-                let distance = predator_transform
-                    .translation
-                    .distance(corpse_transform.translation);
-                if distance < 20.0 && corpse_energy.0 > 10.0 {
-                    // Get less energy from corpses than fresh prey
-                    predator_energy.0 += config.predator_energy_from_prey * 0.7;
-                    commands.entity(corpse_entity).despawn();
-                    break;
+            Meal::Kill(food) => {
+                // The catch becomes a carcass, and this is its first bite
+                let biomass = genomes
+                    .get(food)
+                    .map_or(0.0, |genome| registry.get(feeding.resource).carcass(genome));
+                let taken = (feeding.bite * dt).min(biomass);
+                eaten.insert(food);
+                if let Some(claim) = claim.as_mut() {
+                    claim.0 = Some(food);
                 }
+                make_corpse(
+                    &mut commands,
+                    food,
+                    Corpse::new(feeding.resource, config.corpse_decay_time, biomass - taken),
+                );
+                taken
             }
-        }
-    }
-
-    // Scavengers eating corpses (corpses query excludes scavenger corpses)
-    for (scavenger_transform, mut scavenger_energy) in scavengers.iter_mut() {
-        for (corpse_entity, corpse_transform, corpse_energy) in corpses.iter() {
-            let to_corpse = crate::utils::wrapped_direction(
-                scavenger_transform.translation.xy(),
-                corpse_transform.translation.xy(),
-                &config.world_size,
-            );
-            let distance = to_corpse.length();
-            if distance < 15.0 && corpse_energy.0 > 10.0 {
-                scavenger_energy.0 += config.scavenger_energy_from_corpse;
-                commands.entity(corpse_entity).despawn();
-                break;
+            Meal::Carcass(food) => {
+                let Ok(mut corpse) = corpses.get_mut(food) else {
+                    continue;
+                };
+                let taken = (feeding.bite * dt).min(corpse.biomass);
+                corpse.biomass -= taken;
+                if corpse.biomass <= 0.0 {
+                    eaten.insert(food);
+                    commands.entity(food).despawn();
+                }
+                taken
             }
+        };
+
+        if let Ok(mut energy) = energies.get_mut(eater) {
+            energy.0 += taken.max(0.0) * feeding.efficiency;
         }
     }
 }
@@ -2,33 +2,73 @@ use bevy::prelude::*;
 use rand::Rng;
 
 use crate::components::*;
+use crate::obstacles::Obstacles;
+use crate::resources::*;
+use crate::soil::SoilNutrients;
+use crate::species::{Behavior, sample_range, spawn_organism};
+use crate::utils::chance_per_tick;
 
 // ===== QUERY TYPE ALIASES =====
 
 type EnergyConsumptionQuery<'w, 's> = Query<
     'w,
     's,
-    (&'static mut Energy, &'static Genome, &'static Velocity),
-    Or<(With<Plant>, With<Prey>, With<Predator>, With<Scavenger>)>,
+    (
+        &'static mut Energy,
+        &'static Transform,
+        &'static Genome,
+        &'static Velocity,
+    ),
+    With<SpeciesId>,
 >;
 
 type DeathSystemQuery<'w, 's> = Query<
     'w,
     's,
-    (Entity, &'static Energy, &'static Age),
-    Or<(With<Plant>, With<Prey>, With<Predator>, With<Scavenger>)>,
+    (
+        Entity,
+        &'static Energy,
+        &'static Age,
+        &'static Genome,
+        &'static SpeciesId,
+    ),
+    Without<Producer>,
 >;
 
-type AgeSystemQuery<'w, 's> =
-    Query<'w, 's, &'static mut Age, Or<(With<Plant>, With<Prey>, With<Predator>, With<Scavenger>)>>;
+type AgeSystemQuery<'w, 's> = Query<'w, 's, &'static mut Age, With<SpeciesId>>;
+
+type ParentQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Transform,
+        &'static Energy,
+        &'static Genome,
+        Option<&'static Lineage>,
+        &'static SpeciesId,
+    ),
+>;
 
 // ===== LIFECYCLE SYSTEMS =====
 
-pub fn energy_consumption_system(mut organisms: EnergyConsumptionQuery, time: Res<Time>) {
-    for (mut energy, genome, velocity) in organisms.iter_mut() {
-        let base_cost = genome.metabolism * genome.size * time.delta_secs();
+/// Animals burn energy to live and move, more in the cold, leaving part of
+/// it behind as waste that feeds the soil where they are
+pub fn energy_consumption_system(
+    mut organisms: EnergyConsumptionQuery,
+    mut soil: ResMut<SoilNutrients>,
+    climate: Res<Climate>,
+    config: Res<SimulationConfig>,
+    time: Res<Time>,
+) {
+    for (mut energy, transform, genome, velocity) in organisms.iter_mut() {
+        let base_cost = genome.metabolism * genome.size * climate.metabolism * time.delta_secs();
         let movement_cost = velocity.0.length() * 0.01 * time.delta_secs();
         energy.0 -= base_cost + movement_cost;
+        soil.deposit(
+            transform.translation.truncate(),
+            (base_cost + movement_cost) * config.soil.waste_fraction,
+        );
     }
 }
 
@@ -38,166 +78,180 @@ pub fn age_system(mut organisms: AgeSystemQuery, time: Res<Time>) {
     }
 }
 
-pub fn reproduction_system(
-    mut commands: Commands,
-    plants: Query<(Entity, &Transform, &Energy, &Genome), With<Plant>>,
-    prey: Query<(Entity, &Transform, &Energy, &Genome), With<Prey>>,
-    predators: Query<(Entity, &Transform, &Energy, &Genome), With<Predator>>,
-    scavengers: Query<(Entity, &Transform, &Energy, &Genome), With<Scavenger>>,
-) {
-    let mut rng = rand::rng();
+/// An offspring about to be born, and what it costs its parents
+struct Birth<'a> {
+    position: Vec2,
+    genome: Genome,
+    energy: f32,
+    /// Each parent with its energy after the birth
+    parents: Vec<(Entity, f32)>,
+    parent_lineages: Vec<&'a Lineage>,
+}
 
-    // Count populations for density-dependent reproduction
-    let prey_count = prey.iter().count();
-    let predator_count = predators.iter().count();
-    let scavenger_count = scavengers.iter().count();
+/// Decide which organisms of one species reproduce this tick. Asexual
+/// parents split their energy with a mutated clone; sexual parents must find
+/// an unmated partner above threshold within `mate_search_radius`, each pays
+/// `mating_energy_cost` of its energy, and the offspring is a crossover.
+fn plan_births<'a>(
+    organisms: impl Iterator<
+        Item = (
+            Entity,
+            &'a Transform,
+            &'a Energy,
+            &'a Genome,
+            Option<&'a Lineage>,
+        ),
+    >,
+    rate: f32,
+    spread: f32,
+    sexual: bool,
+    config: &SimulationConfig,
+    rng: &mut SimulationRng,
+    dt: f32,
+) -> Vec<Birth<'a>> {
+    // Only organisms above their threshold can breed, kept in query order
+    let eligible: Vec<_> = organisms
+        .filter(|(_, _, energy, genome, _)| energy.0 > genome.reproduction_threshold)
+        .map(|(entity, transform, energy, genome, lineage)| {
+            (
+                entity,
+                transform.translation.xy(),
+                energy.0,
+                genome,
+                lineage,
+            )
+        })
+        .collect();
 
-    // Plant reproduction
-    for (entity, transform, energy, genome) in plants.iter() {
-        if energy.0 > genome.reproduction_threshold && rng.random_bool(0.01) {
-            let offset = Vec2::new(rng.random_range(-30.0..30.0), rng.random_range(-30.0..30.0));
-            commands.spawn((
-                Plant,
-                genome.clone(),
-                Energy(energy.0 * 0.5),
-                Age(0.0),
-                Transform::from_xyz(
-                    transform.translation.x + offset.x,
-                    transform.translation.y + offset.y,
-                    0.0,
-                ),
-                Sprite {
-                    color: Color::srgb(0.2, 0.8, 0.2),
-                    custom_size: Some(Vec2::splat(8.0)),
-                    ..default()
-                },
-            ));
+    let mut mated = vec![false; eligible.len()];
+    let mut births = Vec::new();
 
-            if let Ok(mut entity_commands) = commands.get_entity(entity) {
-                entity_commands.insert(Energy(energy.0 * 0.5));
-            }
+    for (index, &(entity, position, energy, genome, lineage)) in eligible.iter().enumerate() {
+        if mated[index] || !rng.random_bool(chance_per_tick(rate, dt)) {
+            continue;
         }
-    }
 
-    // Prey reproduction with density-dependent rates
-    // Base rate: 0.5%, doubled to 1% when population < 10
-    let prey_reproduction_rate = if prey_count < 10 { 0.01 } else { 0.005 };
+        let (genome, energy, parents, parent_lineages) = if sexual {
+            let mate = eligible
+                .iter()
+                .enumerate()
+                .filter(|&(other, _)| other != index && !mated[other])
+                .map(|(other, &(_, other_pos, _, _, _))| {
+                    (
+                        other,
+                        crate::utils::wrapped_distance(position, other_pos, &config.world_size),
+                    )
+                })
+                .filter(|&(_, distance)| distance <= config.mate_search_radius)
+                .min_by(|a, b| a.1.total_cmp(&b.1));
+            let Some((mate_index, _)) = mate else {
+                continue;
+            };
 
-    for (entity, transform, energy, genome) in prey.iter() {
-        if energy.0 > genome.reproduction_threshold && rng.random_bool(prey_reproduction_rate) {
-            let offset = Vec2::new(rng.random_range(-20.0..20.0), rng.random_range(-20.0..20.0));
-            commands.spawn((
-                Prey,
+            mated[index] = true;
+            mated[mate_index] = true;
+            let (mate, _, mate_energy, mate_genome, mate_lineage) = eligible[mate_index];
+            let cost = config.mating_energy_cost;
+            (
+                genome.crossover(mate_genome, rng),
+                (energy + mate_energy) * cost,
+                vec![
+                    (entity, energy * (1.0 - cost)),
+                    (mate, mate_energy * (1.0 - cost)),
+                ],
+                lineage.into_iter().chain(mate_lineage).collect(),
+            )
+        } else {
+            (
                 genome.clone(),
-                Energy(energy.0 * 0.5),
-                Age(0.0),
-                Velocity(Vec2::ZERO),
-                Stamina::default(),
-                Transform::from_xyz(
-                    transform.translation.x + offset.x,
-                    transform.translation.y + offset.y,
-                    1.0,
-                ),
-                Sprite {
-                    color: Color::srgb(0.3, 0.3, 0.9),
-                    custom_size: Some(Vec2::splat(12.0)),
-                    ..default()
-                },
-            ));
+                energy * 0.5,
+                vec![(entity, energy * 0.5)],
+                lineage.into_iter().collect(),
+            )
+        };
 
-            if let Ok(mut entity_commands) = commands.get_entity(entity) {
-                entity_commands.insert(Energy(energy.0 * 0.5));
-            }
-        }
+        let offset = Vec2::new(
+            sample_range((-spread, spread), rng),
+            sample_range((-spread, spread), rng),
+        );
+        births.push(Birth {
+            position: position + offset,
+            genome: genome.mutated(&config.mutation, rng),
+            energy,
+            parents,
+            parent_lineages,
+        });
     }
 
-    // Predator reproduction with density-dependent rates
-    // Base rate: 0.3%, doubled to 0.6% when population < 10
-    let predator_reproduction_rate = if predator_count < 10 { 0.006 } else { 0.003 };
-
-    for (entity, transform, energy, genome) in predators.iter() {
-        if energy.0 > genome.reproduction_threshold && rng.random_bool(predator_reproduction_rate) {
-            let offset = Vec2::new(rng.random_range(-20.0..20.0), rng.random_range(-20.0..20.0));
-            let spawn_pos = Vec2::new(
-                transform.translation.x + offset.x,
-                transform.translation.y + offset.y,
-            );
-
-            // Generate initial exploration waypoint for offspring
-            let waypoint_angle = rng.random_range(0.0..std::f32::consts::TAU);
-            let waypoint_distance = rng.random_range(100.0..200.0);
-            let waypoint_target = Vec2::new(
-                spawn_pos.x + waypoint_angle.cos() * waypoint_distance,
-                spawn_pos.y + waypoint_angle.sin() * waypoint_distance,
-            );
-
-            commands.spawn((
-                Predator,
-                genome.clone(),
-                Energy(energy.0 * 0.5),
-                Age(0.0),
-                Velocity(Vec2::ZERO),
-                HuntTarget(None),
-                ExplorationWaypoint {
-                    target: waypoint_target,
-                    reached_threshold: 30.0,
-                },
-                Transform::from_xyz(spawn_pos.x, spawn_pos.y, 2.0),
-                Sprite {
-                    color: Color::srgb(0.9, 0.2, 0.2),
-                    custom_size: Some(Vec2::splat(16.0)),
-                    ..default()
-                },
-            ));
+    births
+}
 
-            if let Ok(mut entity_commands) = commands.get_entity(entity) {
-                entity_commands.insert(Energy(energy.0 * 0.5));
-            }
+/// Pay each parent's share of a birth
+fn charge_parents(commands: &mut Commands, birth: &Birth) {
+    for &(parent, remaining) in &birth.parents {
+        if let Ok(mut entity_commands) = commands.get_entity(parent) {
+            entity_commands.insert(Energy(remaining));
         }
     }
+}
 
-    // Scavenger reproduction with density-dependent rates
-    // Base rate: 0.4%, doubled to 0.8% when population < 10
-    let scavenger_reproduction_rate = if scavenger_count < 10 { 0.008 } else { 0.004 };
+#[allow(clippy::too_many_arguments)]
+pub fn reproduction_system(
+    mut commands: Commands,
+    organisms: ParentQuery,
+    obstacles: Res<Obstacles>,
+    config: Res<SimulationConfig>,
+    mut rng: ResMut<SimulationRng>,
+    mut lineage: ResMut<LineageTracker>,
+    clock: Res<SimulationClock>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
 
-    for (entity, transform, energy, genome) in scavengers.iter() {
-        if energy.0 > genome.reproduction_threshold && rng.random_bool(scavenger_reproduction_rate)
-        {
-            let offset = Vec2::new(rng.random_range(-20.0..20.0), rng.random_range(-20.0..20.0));
-            let spawn_pos = Vec2::new(
-                transform.translation.x + offset.x,
-                transform.translation.y + offset.y,
-            );
+    // Count populations for density-dependent reproduction
+    let mut counts = vec![0; config.species.len()];
+    for (.., species) in organisms.iter() {
+        counts[species.0] += 1;
+    }
 
-            // Generate initial exploration waypoint for offspring
-            let waypoint_angle = rng.random_range(0.0..std::f32::consts::TAU);
-            let waypoint_distance = rng.random_range(100.0..200.0);
-            let waypoint_target = Vec2::new(
-                spawn_pos.x + waypoint_angle.cos() * waypoint_distance,
-                spawn_pos.y + waypoint_angle.sin() * waypoint_distance,
-            );
+    for (species, species_config) in config.species.iter() {
+        // Producers always spread asexually and get no small-population boost
+        let is_producer = species_config.behavior == Behavior::Producer;
+        let rate = if !is_producer && counts[species.0] < config.low_population_threshold {
+            species_config.reproduction_rate * config.low_population_reproduction_boost
+        } else {
+            species_config.reproduction_rate
+        };
 
-            commands.spawn((
-                Scavenger,
-                genome.clone(),
-                Energy(energy.0 * 0.5),
-                Age(0.0),
-                Velocity(Vec2::ZERO),
-                ExplorationWaypoint {
-                    target: waypoint_target,
-                    reached_threshold: 30.0,
-                },
-                Transform::from_xyz(spawn_pos.x, spawn_pos.y, 1.5),
-                Sprite {
-                    color: Color::srgb(0.7, 0.5, 0.2),
-                    custom_size: Some(Vec2::splat(14.0)),
-                    ..default()
-                },
-            ));
+        let parents = organisms
+            .iter()
+            .filter(|&(.., &parent_species)| parent_species == species)
+            .map(|(entity, transform, energy, genome, lineage, _)| {
+                (entity, transform, energy, genome, lineage)
+            });
 
-            if let Ok(mut entity_commands) = commands.get_entity(entity) {
-                entity_commands.insert(Energy(energy.0 * 0.5));
-            }
+        for birth in plan_births(
+            parents,
+            rate,
+            species_config.offspring_spread,
+            config.sexual_reproduction && !is_producer,
+            &config,
+            &mut rng,
+            dt,
+        ) {
+            charge_parents(&mut commands, &birth);
+            let offspring_lineage = lineage.offspring(&birth.parent_lineages, clock.elapsed);
+            spawn_organism(
+                &mut commands,
+                &config.species,
+                species,
+                // Offspring landing in an obstacle are born at its edge
+                obstacles.nearest_open(birth.position),
+                birth.genome,
+                birth.energy,
+                offspring_lineage,
+                &mut *rng,
+            );
         }
     }
 }
@@ -205,71 +259,61 @@ pub fn reproduction_system(
 pub fn death_system(
     mut commands: Commands,
     organisms: DeathSystemQuery,
-    prey_query: Query<&Transform, With<Prey>>,
-    predator_query: Query<&Transform, With<Predator>>,
-    scavenger_query: Query<&Transform, With<Scavenger>>,
+    config: Res<SimulationConfig>,
 ) {
-    for (entity, energy, age) in organisms.iter() {
-        if energy.0 <= 0.0 || age.0 > 300.0 {
-            // Convert to corpse instead of despawning immediately
-            // Corpses provide food and decay over time
-            let corpse_decay_time = 30.0; // 30 seconds before corpse despawns
-
-            // Change sprite color to indicate death
-            if prey_query.get(entity).is_ok() {
-                commands
-                    .entity(entity)
-                    .remove::<Prey>()
-                    .remove::<Velocity>()
-                    .remove::<Stamina>()
-                    .insert(Corpse::new(corpse_decay_time))
-                    .insert(Sprite {
-                        color: Color::srgb(0.5, 0.5, 0.5), // Gray for corpse
-                        custom_size: Some(Vec2::splat(12.0)),
-                        ..default()
-                    });
-            } else if predator_query.get(entity).is_ok() {
-                commands
-                    .entity(entity)
-                    .remove::<Predator>()
-                    .remove::<Velocity>()
-                    .remove::<HuntTarget>()
-                    .remove::<ExplorationWaypoint>()
-                    .insert(Corpse::new(corpse_decay_time))
-                    .insert(Sprite {
-                        color: Color::srgb(0.6, 0.3, 0.3), // Dark red for predator corpse
-                        custom_size: Some(Vec2::splat(16.0)),
-                        ..default()
-                    });
-            } else if scavenger_query.get(entity).is_ok() {
-                commands
-                    .entity(entity)
-                    .remove::<Scavenger>()
-                    .remove::<Velocity>()
-                    .remove::<ExplorationWaypoint>()
-                    .insert(Corpse::new(corpse_decay_time))
-                    .insert(Sprite {
-                        color: Color::srgb(0.5, 0.4, 0.2), // Dark brown for scavenger corpse
-                        custom_size: Some(Vec2::splat(14.0)),
-                        ..default()
-                    });
-            }
+    // Producers never die; animals starve or die of old age
+    for (entity, energy, age, genome, &species) in organisms.iter() {
+        if energy.0 <= 0.0 || age.0 > config.max_age {
+            // Convert to corpse instead of despawning immediately; its food
+            // value is its body, however starved it was
+            let biomass = config.species.get(species).carcass(genome);
+            make_corpse(
+                &mut commands,
+                entity,
+                Corpse::new(species, config.corpse_decay_time, biomass),
+            );
         }
     }
 }
 
+/// Turn a living organism into `corpse`, dropping everything that made it
+/// behave like its species
+pub fn make_corpse(commands: &mut Commands, entity: Entity, corpse: Corpse) {
+    commands
+        .entity(entity)
+        .remove::<(
+            SpeciesId,
+            Energy,
+            Forager,
+            Hunter,
+            Scavenger,
+            Omnivore,
+            Velocity,
+            Stamina,
+            HuntTarget,
+            ExplorationWaypoint,
+        )>()
+        .insert(corpse);
+}
+
+/// Corpses rot linearly over their decay time, returning the biomass
+/// nobody ate to the soil beneath them
 pub fn corpse_decay_system(
     mut commands: Commands,
-    mut corpses: Query<(Entity, &mut Corpse, &mut Sprite)>,
+    mut corpses: Query<(Entity, &Transform, &mut Corpse)>,
+    mut soil: ResMut<SoilNutrients>,
     time: Res<Time>,
 ) {
-    for (entity, mut corpse, mut sprite) in corpses.iter_mut() {
-        corpse.decay_timer -= time.delta_secs();
-
-        // Gradually fade out the corpse as it decays
-        let decay_progress = corpse.decay_timer / corpse.max_decay_time;
-        let alpha = decay_progress.max(0.2); // Keep minimum alpha of 0.2
-        sprite.color = sprite.color.with_alpha(alpha);
+    let dt = time.delta_secs();
+    for (entity, transform, mut corpse) in corpses.iter_mut() {
+        let rotted = if corpse.decay_timer > dt {
+            corpse.biomass * dt / corpse.decay_timer
+        } else {
+            corpse.biomass
+        };
+        corpse.biomass -= rotted;
+        soil.deposit(transform.translation.truncate(), rotted);
+        corpse.decay_timer -= dt;
 
         // Despawn when fully decayed
         if corpse.decay_timer <= 0.0 {
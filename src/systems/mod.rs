@@ -1,20 +1,28 @@
 // ===== SYSTEM MODULES =====
 
+pub mod config_reload;
 pub mod environment;
 pub mod input;
 pub mod interaction;
 pub mod lifecycle;
 pub mod movement;
+pub mod persistence;
+pub mod render;
 pub mod setup;
+pub mod spatial;
 pub mod stats;
 pub mod ui;
 
 // Re-export systems for easy access
+pub use config_reload::*;
 pub use environment::*;
 pub use input::*;
 pub use interaction::*;
 pub use lifecycle::*;
 pub use movement::*;
+pub use persistence::*;
+pub use render::*;
 pub use setup::*;
+pub use spatial::*;
 pub use stats::*;
 pub use ui::*;
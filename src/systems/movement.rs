@@ -2,33 +2,71 @@ use bevy::prelude::*;
 use rand::Rng;
 
 use crate::components::*;
+use crate::obstacles::Obstacles;
+use crate::pathfinding::FlowFields;
 use crate::resources::*;
+use crate::spatial::{GridLayer, SpatialGrid};
+use crate::species::{Feeding, FeedingMode, sample_range};
+use crate::terrain::BiomeMap;
 use crate::utils::*;
 
 // ===== HELPER FUNCTIONS =====
 
-/// Calculate speed multiplier based on age
-/// - Age 0-240: Normal speed (1.0)
-/// - Age 240-270: Gradual slowdown (1.0 -> 0.2)
-/// - Age 270-300: Very slow (0.2 -> 0.0)
-fn age_speed_multiplier(age: f32) -> f32 {
-    const SLOWDOWN_START: f32 = 240.0;
-    const VERY_SLOW_START: f32 = 270.0;
-    const MAX_AGE: f32 = 300.0;
+/// Calculate speed multiplier based on age, relative to the maximum age
+/// - First 80% of life: Normal speed (1.0)
+/// - 80-90%: Gradual slowdown (1.0 -> 0.2)
+/// - 90-100%: Very slow (0.2 -> 0.0)
+fn age_speed_multiplier(age: f32, max_age: f32) -> f32 {
+    let slowdown_start = max_age * 0.8;
+    let very_slow_start = max_age * 0.9;
 
-    if age < SLOWDOWN_START {
+    if age < slowdown_start {
         1.0
-    } else if age < VERY_SLOW_START {
+    } else if age < very_slow_start {
         // Linear interpolation from 1.0 to 0.2
-        1.0 - ((age - SLOWDOWN_START) / (VERY_SLOW_START - SLOWDOWN_START)) * 0.8
-    } else if age < MAX_AGE {
+        1.0 - ((age - slowdown_start) / (very_slow_start - slowdown_start)) * 0.8
+    } else if age < max_age {
         // Linear interpolation from 0.2 to 0.0
-        0.2 - ((age - VERY_SLOW_START) / (MAX_AGE - VERY_SLOW_START)) * 0.2
+        0.2 - ((age - very_slow_start) / (max_age - very_slow_start)) * 0.2
     } else {
         0.0
     }
 }
 
+/// Fraction of the way velocity turns toward its target each tick.
+/// Tuned as 10% per tick at 60 Hz and rescaled so steering feels the same
+/// at any tick rate.
+fn steering_blend(dt: f32) -> f32 {
+    1.0 - 0.9_f32.powf(dt * 60.0)
+}
+
+/// A new exploration target, somewhat beyond vision range, wrapped into the
+/// world. Targets that fall inside an obstacle are dropped as if reached.
+fn next_waypoint(position: Vec2, vision_range: f32, world_size: &Vec2, rng: &mut impl Rng) -> Vec2 {
+    let angle = rng.random_range(0.0..std::f32::consts::TAU);
+    let distance = sample_range((vision_range * 0.8, vision_range * 1.5), rng);
+    let mut target = position + Vec2::new(angle.cos(), angle.sin()) * distance;
+
+    // Wrap waypoint to world bounds
+    if target.x > world_size.x / 2.0 {
+        target.x -= world_size.x;
+    } else if target.x < -world_size.x / 2.0 {
+        target.x += world_size.x;
+    }
+    if target.y > world_size.y / 2.0 {
+        target.y -= world_size.y;
+    } else if target.y < -world_size.y / 2.0 {
+        target.y += world_size.y;
+    }
+    target
+}
+
+/// `EntityRng` streams, one per movement system
+const PREY_STREAM: u64 = 1;
+const PREDATOR_STREAM: u64 = 2;
+const SCAVENGER_STREAM: u64 = 3;
+const OMNIVORE_STREAM: u64 = 4;
+
 // ===== QUERY TYPE ALIASES =====
 
 type PreyMovementQuery<'w, 's> = Query<
@@ -42,8 +80,9 @@ type PreyMovementQuery<'w, 's> = Query<
         &'static Genome,
         &'static Energy,
         &'static Age,
+        &'static SpeciesId,
     ),
-    (With<Prey>, Without<Corpse>),
+    With<Forager>,
 >;
 
 type PredatorHuntingQuery<'w, 's> = Query<
@@ -56,13 +95,18 @@ type PredatorHuntingQuery<'w, 's> = Query<
         &'static mut HuntTarget,
         &'static mut ExplorationWaypoint,
         &'static Genome,
+        &'static Energy,
         &'static Age,
+        &'static SpeciesId,
     ),
-    (With<Predator>, Without<Corpse>),
+    With<Hunter>,
 >;
 
-type PreyTargetQuery<'w, 's> =
-    Query<'w, 's, (Entity, &'static Transform), (With<Prey>, Without<Predator>)>;
+/// Every living organism a hunter might chase
+type HuntTargetQuery<'w, 's> = Query<'w, 's, (Entity, &'static Transform, &'static SpeciesId)>;
+
+/// Carcasses a hunter might be feeding on
+type CarcassQuery<'w, 's> = Query<'w, 's, (Entity, &'static Transform, &'static Corpse)>;
 
 type ScavengerMovementQuery<'w, 's> = Query<
     'w,
@@ -74,406 +118,764 @@ type ScavengerMovementQuery<'w, 's> = Query<
         &'static mut ExplorationWaypoint,
         &'static Genome,
         &'static Age,
+        &'static SpeciesId,
     ),
-    (With<Scavenger>, Without<Corpse>),
+    With<Scavenger>,
 >;
 
-type CorpseTargetQuery<'w, 's> = Query<
+type OmnivoreMovementQuery<'w, 's> = Query<
     'w,
     's,
-    (Entity, &'static Transform, &'static Energy),
     (
-        With<Corpse>,
-        Without<Scavenger>,
-        Or<(With<Prey>, With<Predator>)>,
+        Entity,
+        &'static mut Transform,
+        &'static mut Velocity,
+        &'static mut ExplorationWaypoint,
+        &'static Genome,
+        &'static Energy,
+        &'static Age,
+        &'static SpeciesId,
     ),
+    With<Omnivore>,
 >;
 
+/// Living organisms as food: what they hold, how big they are and what they are
+type FoodQuery<'w, 's> = Query<'w, 's, (&'static Energy, &'static Genome, &'static SpeciesId)>;
+
+type CorpseQuery<'w, 's> = Query<'w, 's, &'static Corpse>;
+
+/// For each species, the grid layers of the species it eats alive
+fn diet_layers(config: &SimulationConfig) -> Vec<Vec<GridLayer>> {
+    let registry = &config.species;
+    registry
+        .iter()
+        .map(|(species, _)| {
+            config
+                .food_web
+                .diet(registry, species)
+                .into_iter()
+                .map(GridLayer::Living)
+                .collect()
+        })
+        .collect()
+}
+
+/// For each species, how it feeds on the living
+fn living_feedings(config: &SimulationConfig) -> Vec<Vec<Feeding>> {
+    let registry = &config.species;
+    registry
+        .iter()
+        .map(|(species, _)| {
+            config
+                .food_web
+                .feedings(registry, species)
+                .into_iter()
+                .filter(|feeding| feeding.mode != FeedingMode::Scavenge)
+                .collect()
+        })
+        .collect()
+}
+
+/// For each species, how far it sees at this time of day relative to daylight
+fn night_vision(config: &SimulationConfig, climate: &Climate) -> Vec<f32> {
+    config
+        .species
+        .iter()
+        .map(|(_, species)| config.night.vision(climate.darkness(), species.nocturnal))
+        .collect()
+}
+
+/// Whether living `food` is worth heading for under one of `feedings`:
+/// something `eating_system` would let a consumer of `consumer_size` eat
+fn worth_eating(feedings: &[Feeding], foods: &FoodQuery, food: Entity, consumer_size: f32) -> bool {
+    foods.get(food).is_ok_and(|(energy, genome, species)| {
+        feedings.iter().any(|feeding| {
+            feeding.resource == *species
+                && energy.0 > feeding.min_energy
+                && feeding.fits(consumer_size, genome.size)
+        })
+    })
+}
+
 // ===== MOVEMENT SYSTEMS =====
 
+/// Foragers flee anything that eats them, flock with their own species and
+/// graze on their diet. Well-fed foragers rest in huddles through the night.
+#[allow(clippy::too_many_arguments)]
 pub fn prey_movement_system(
     mut prey: PreyMovementQuery,
-    plants: Query<&Transform, (With<Plant>, Without<Prey>)>,
-    predators: Query<&Transform, (With<Predator>, Without<Prey>)>,
+    foods: FoodQuery,
+    grid: Res<SpatialGrid>,
+    biomes: Res<BiomeMap>,
+    obstacles: Res<Obstacles>,
+    paths: Res<FlowFields>,
+    climate: Res<Climate>,
     config: Res<SimulationConfig>,
+    tick_seed: Res<TickSeed>,
     time: Res<Time>,
 ) {
-    let mut rng = rand::rng();
+    // Velocities of all foragers for flocking alignment
+    let prey_velocities: std::collections::HashMap<Entity, Vec2> =
+        prey.iter().map(|(e, _, v, ..)| (e, v.0)).collect();
 
-    // Collect all prey data for flocking calculations
-    let prey_data: Vec<(Entity, Vec2, Vec2)> = prey
+    let registry = &config.species;
+    let threats: Vec<Vec<GridLayer>> = registry
         .iter()
-        .map(|(e, t, v, _, _, _, _)| (e, t.translation.xy(), v.0))
+        .map(|(species, _)| {
+            config
+                .food_web
+                .threats(registry, species)
+                .into_iter()
+                .map(GridLayer::Living)
+                .collect()
+        })
         .collect();
+    let food = diet_layers(&config);
+    let feedings = living_feedings(&config);
+    let night_vision = night_vision(&config, &climate);
+
+    let dt = time.delta_secs();
+
+    prey.par_iter_mut().for_each(
+        |(entity, mut transform, mut velocity, mut stamina, genome, energy, age, &species)| {
+            let mut rng = tick_seed.entity_rng(PREY_STREAM, entity);
+            let mut desired_direction = Vec2::ZERO;
+            let mut is_fleeing = false;
+            let mut threat_level: f32 = 0.0;
+            let current_pos = transform.translation.xy();
+            // The land underfoot slows animals down and limits how far they see
+            let biome = config.terrain.biome(biomes.at(current_pos));
+            let vision_range = genome.vision_range * biome.visibility * night_vision[species.0];
+
+            // Flee from predators (highest priority) using wrapped distances
+            let flee_radius = vision_range * 1.5;
+            for (_, predator_pos, distance) in
+                grid.within_any(&threats[species.0], current_pos, flee_radius)
+            {
+                let to_predator =
+                    crate::utils::wrapped_direction(current_pos, predator_pos, &config.world_size);
+                if distance < flee_radius {
+                    let flee_strength = (vision_range * 1.5 - distance) / vision_range;
+                    desired_direction -= to_predator.normalize_or_zero() * flee_strength * 2.0;
+                    is_fleeing = true;
+                    threat_level = threat_level.max(flee_strength);
+                }
+            }
 
-    for (entity, mut transform, mut velocity, mut stamina, genome, energy, age) in prey.iter_mut() {
-        let mut desired_direction = Vec2::ZERO;
-        let mut is_fleeing = false;
-        let mut threat_level: f32 = 0.0;
-        let current_pos = transform.translation.xy();
+            // Well-fed foragers rest through the night, huddled together
+            let resting = climate.is_night()
+                && !is_fleeing
+                && energy.0 >= genome.reproduction_threshold * config.night.rest_energy;
+
+            // Flocking behavior (boids algorithm)
+            if !is_fleeing || threat_level < 0.7 {
+                let crowding = &config.crowding;
+                let flocking_radius = if resting {
+                    vision_range.max(crowding.flocking_radius)
+                } else {
+                    crowding.flocking_radius
+                };
+                let mut separation = Vec2::ZERO;
+                let mut alignment = Vec2::ZERO;
+                let mut cohesion = Vec2::ZERO;
+                let mut neighbor_count = 0;
+
+                for (other_entity, other_pos, distance) in
+                    grid.within(GridLayer::Living(species), current_pos, flocking_radius)
+                {
+                    if other_entity == entity {
+                        continue;
+                    }
 
-        // Flee from predators (highest priority) using wrapped distances
-        for predator_transform in predators.iter() {
-            let to_predator = crate::utils::wrapped_direction(
-                current_pos,
-                predator_transform.translation.xy(),
-                &config.world_size,
-            );
-            let distance = to_predator.length();
-            if distance < genome.vision_range * 1.5 {
-                let flee_strength = (genome.vision_range * 1.5 - distance) / genome.vision_range;
-                desired_direction -= to_predator.normalize() * flee_strength * 2.0;
-                is_fleeing = true;
-                threat_level = threat_level.max(flee_strength);
-            }
-        }
+                    let to_other =
+                        crate::utils::wrapped_direction(current_pos, other_pos, &config.world_size);
 
-        // Flocking behavior (boids algorithm)
-        if !is_fleeing || threat_level < 0.7 {
-            let flocking_radius = 60.0;
-            let mut separation = Vec2::ZERO;
-            let mut alignment = Vec2::ZERO;
-            let mut cohesion = Vec2::ZERO;
-            let mut neighbor_count = 0;
-
-            for (other_entity, other_pos, other_vel) in &prey_data {
-                if *other_entity == entity {
-                    continue;
-                }
+                    if distance < flocking_radius && distance > 0.1 {
+                        neighbor_count += 1;
 
-                let to_other = *other_pos - transform.translation.xy();
-                let distance = to_other.length();
+                        // Separation: avoid crowding
+                        let spacing = crowding.forager_spacing;
+                        if distance < spacing {
+                            separation -=
+                                to_other.normalize_or_zero() * (spacing - distance) / spacing;
+                        }
 
-                if distance < flocking_radius && distance > 0.1 {
-                    neighbor_count += 1;
+                        // Alignment: match velocity
+                        alignment += prey_velocities
+                            .get(&other_entity)
+                            .copied()
+                            .unwrap_or_default();
 
-                    // Separation: avoid crowding
-                    if distance < 30.0 {
-                        separation -= to_other.normalize() * (30.0 - distance) / 30.0;
+                        // Cohesion: move toward center of group
+                        cohesion += to_other;
                     }
+                }
 
-                    // Alignment: match velocity
-                    alignment += *other_vel;
-
-                    // Cohesion: move toward center of group
-                    cohesion += to_other;
+                if neighbor_count > 0 {
+                    let neighbor_count_f = neighbor_count as f32;
+                    separation /= neighbor_count_f;
+                    alignment = (alignment / neighbor_count_f).normalize_or_zero();
+                    cohesion = (cohesion / neighbor_count_f).normalize_or_zero();
+
+                    // Weight flocking behaviors - less when fleeing
+                    let flocking_weight = if is_fleeing { 0.2 } else { 0.6 };
+                    desired_direction += separation * 1.5 * flocking_weight;
+                    desired_direction += alignment * 0.5 * flocking_weight;
+                    let cohesion_weight = if resting {
+                        0.8 * config.night.rest_cohesion
+                    } else {
+                        0.8
+                    };
+                    desired_direction += cohesion * cohesion_weight * flocking_weight;
                 }
             }
 
-            if neighbor_count > 0 {
-                let neighbor_count_f = neighbor_count as f32;
-                separation /= neighbor_count_f;
-                alignment = (alignment / neighbor_count_f).normalize_or_zero();
-                cohesion = (cohesion / neighbor_count_f).normalize_or_zero();
-
-                // Weight flocking behaviors - less when fleeing
-                let flocking_weight = if is_fleeing { 0.2 } else { 0.6 };
-                desired_direction += separation * 1.5 * flocking_weight;
-                desired_direction += alignment * 0.5 * flocking_weight;
-                desired_direction += cohesion * 0.8 * flocking_weight;
+            // Determine speed multiplier based on stamina and threat
+            let mut speed_multiplier = if resting {
+                config.night.rest_speed
+            } else {
+                1.0
+            };
+            let can_sprint = stamina.current > 10.0 && energy.0 > 20.0;
+
+            if is_fleeing && can_sprint && threat_level > 0.5 {
+                // Sprint when threatened and have stamina
+                speed_multiplier = 2.5;
+                stamina.current -= 30.0 * dt; // Drain stamina quickly
+            } else {
+                // Regenerate stamina when not sprinting
+                stamina.current = (stamina.current + stamina.regen_rate * dt).min(stamina.max);
             }
-        }
 
-        // Determine speed multiplier based on stamina and threat
-        let mut speed_multiplier = 1.0;
-        let can_sprint = stamina.current > 10.0 && energy.0 > 20.0;
-
-        if is_fleeing && can_sprint && threat_level > 0.5 {
-            // Sprint when threatened and have stamina
-            speed_multiplier = 2.5;
-            stamina.current -= 30.0 * time.delta_secs(); // Drain stamina quickly
-        } else {
-            // Regenerate stamina when not sprinting
-            stamina.current =
-                (stamina.current + stamina.regen_rate * time.delta_secs()).min(stamina.max);
-        }
+            // Move towards nearest food if hungry and not fleeing strongly using wrapped distances
+            if !resting
+                && desired_direction.length() < 0.5
+                && threat_level < 0.3
+                && let Some((_, plant_pos, distance)) =
+                    grid.nearest(&food[species.0], current_pos, vision_range, |plant| {
+                        worth_eating(&feedings[species.0], &foods, plant, genome.size)
+                    })
+                && distance < vision_range
+            {
+                desired_direction += paths.heading(current_pos, plant_pos) * 0.5;
+            }
 
-        // Move towards nearest plant if hungry and not fleeing strongly using wrapped distances
-        if desired_direction.length() < 0.5
-            && threat_level < 0.3
-            && let Some(nearest_plant) = plants.iter().min_by_key(|p| {
-                crate::utils::wrapped_distance(current_pos, p.translation.xy(), &config.world_size)
-                    as i32
-            })
-        {
-            let to_plant = crate::utils::wrapped_direction(
-                current_pos,
-                nearest_plant.translation.xy(),
-                &config.world_size,
-            );
-            if to_plant.length() < genome.vision_range {
-                desired_direction += to_plant.normalize() * 0.5;
+            // Random wander if no strong stimulus
+            if !resting && desired_direction.length() < 0.1 {
+                desired_direction =
+                    Vec2::new(rng.random_range(-1.0..1.0), rng.random_range(-1.0..1.0));
             }
-        }
 
-        // Random wander if no strong stimulus
-        if desired_direction.length() < 0.1 {
-            desired_direction = Vec2::new(rng.random_range(-1.0..1.0), rng.random_range(-1.0..1.0));
-        }
+            // Slide around obstacles rather than into them
+            desired_direction = obstacles.steer(
+                current_pos,
+                desired_direction,
+                config.obstacles.avoidance_distance,
+            );
 
-        // Apply age-based speed reduction
-        let age_multiplier = age_speed_multiplier(age.0);
-        let target_speed = genome.speed * speed_multiplier * age_multiplier;
-        velocity.0 = velocity
-            .0
-            .lerp(desired_direction.normalize() * target_speed, 0.1);
-        transform.translation += velocity.0.extend(0.0) * time.delta_secs();
+            // Apply age-based speed reduction
+            let age_multiplier = age_speed_multiplier(age.0, config.max_age);
+            let target_speed = genome.speed * biome.speed * speed_multiplier * age_multiplier;
+            velocity.0 = velocity.0.lerp(
+                desired_direction.normalize_or_zero() * target_speed,
+                steering_blend(dt),
+            );
+            transform.translation += velocity.0.extend(0.0) * dt;
 
-        // Wrap around world
-        wrap_position(&mut transform.translation, &config.world_size);
-    }
+            // Wrap around world
+            wrap_position(&mut transform.translation, &config.world_size);
+            obstacles.keep_out(&mut transform.translation);
+        },
+    );
 }
 
+/// Hunters claim a living target from their diet (avoiding targets that
+/// already have several hunters) and explore between waypoints otherwise.
+/// Once the target is caught they stay on its carcass until they are full.
+#[allow(clippy::too_many_arguments)]
 pub fn predator_hunting_system(
-    mut predators: PredatorHuntingQuery,
-    prey: PreyTargetQuery,
+    mut organisms: ParamSet<(PredatorHuntingQuery, HuntTargetQuery, CarcassQuery)>,
+    foods: FoodQuery,
+    grid: Res<SpatialGrid>,
+    biomes: Res<BiomeMap>,
+    obstacles: Res<Obstacles>,
+    paths: Res<FlowFields>,
+    climate: Res<Climate>,
     config: Res<SimulationConfig>,
+    tick_seed: Res<TickSeed>,
     time: Res<Time>,
 ) {
-    let mut rng = rand::rng();
-
-    // Collect prey positions and valid prey set
-    let prey_entities: std::collections::HashSet<Entity> = prey.iter().map(|(e, _)| e).collect();
-    let prey_positions: std::collections::HashMap<Entity, Vec2> =
-        prey.iter().map(|(e, t)| (e, t.translation.xy())).collect();
+    // Positions of everything alive, after prey has moved
+    let prey_positions: std::collections::HashMap<Entity, (Vec2, SpeciesId)> = organisms
+        .p1()
+        .iter()
+        .map(|(e, t, &species)| (e, (t.translation.xy(), species)))
+        .collect();
+    let carcasses: std::collections::HashMap<Entity, (Vec2, SpeciesId)> = organisms
+        .p2()
+        .iter()
+        .filter(|(_, _, corpse)| corpse.biomass > 0.0)
+        .map(|(e, t, corpse)| (e, (t.translation.xy(), corpse.species)))
+        .collect();
 
-    // Collect predator data for separation calculations
-    let predator_data: Vec<(Entity, Vec2, Option<Entity>)> = predators
+    let registry = &config.species;
+    let diets: Vec<Vec<SpeciesId>> = registry
         .iter()
-        .map(|(e, t, _, ht, _, _, _)| (e, t.translation.xy(), ht.0))
+        .map(|(species, _)| config.food_web.diet(registry, species))
         .collect();
+    let diet_layers = diet_layers(&config);
+    let feedings = living_feedings(&config);
+    let night_vision = night_vision(&config, &climate);
+
+    let mut predators = organisms.p0();
 
     // Count hunters per prey
     let mut hunters_per_prey: std::collections::HashMap<Entity, usize> =
         std::collections::HashMap::new();
-    for (_, _, target) in &predator_data {
-        if let Some(t) = target {
-            *hunters_per_prey.entry(*t).or_insert(0) += 1;
+    for (_, _, _, hunt_target, ..) in predators.iter() {
+        if let Some(target) = hunt_target.0 {
+            *hunters_per_prey.entry(target).or_insert(0) += 1;
         }
     }
 
-    // Update predators
-    for (
-        _predator_entity,
-        mut transform,
-        mut velocity,
-        mut hunt_target,
-        mut waypoint,
-        genome,
-        age,
-    ) in predators.iter_mut()
-    {
-        // Validate target
-        if let Some(target) = hunt_target.0
-            && !prey_entities.contains(&target)
-        {
-            hunt_target.0 = None;
-        }
-
-        let current_pos = transform.translation.xy();
+    let dt = time.delta_secs();
 
-        // Check if current target is valid and not overcrowded
-        let mut need_new_target = false;
-        if let Some(target) = hunt_target.0 {
-            if let Some(target_pos) = prey_positions.get(&target) {
-                let distance =
-                    crate::utils::wrapped_distance(current_pos, *target_pos, &config.world_size);
-                let hunter_count = hunters_per_prey.get(&target).copied().unwrap_or(0);
+    // Update predators
+    predators.par_iter_mut().for_each(
+        |(
+            entity,
+            mut transform,
+            mut velocity,
+            mut hunt_target,
+            mut waypoint,
+            genome,
+            energy,
+            age,
+            &species,
+        )| {
+            let mut rng = tick_seed.entity_rng(PREDATOR_STREAM, entity);
+            let current_pos = transform.translation.xy();
+            let biome = config.terrain.biome(biomes.at(current_pos));
+            let vision_range = genome.vision_range * biome.visibility * night_vision[species.0];
+
+            // A caught target stays claimed while there is meat and appetite
+            let carcass = hunt_target
+                .0
+                .and_then(|target| carcasses.get(&target))
+                .filter(|(_, carcass_species)| {
+                    diets[species.0].contains(carcass_species)
+                        && energy.0
+                            < genome.reproduction_threshold * config.crowding.carcass_appetite
+                })
+                .map(|&(position, _)| position);
+
+            // Validate target: still alive and still something this species eats
+            if let Some(target) = hunt_target.0
+                && carcass.is_none()
+                && !prey_positions
+                    .get(&target)
+                    .is_some_and(|(_, target_species)| diets[species.0].contains(target_species))
+            {
+                hunt_target.0 = None;
+            }
 
-                // Switch if too many hunters (max 3) or target too far
-                if hunter_count > 3 || distance > genome.vision_range * 2.0 {
+            // Check if current target is valid and not overcrowded
+            let mut need_new_target = false;
+            if let Some(target) = hunt_target.0 {
+                if let Some((target_pos, _)) = prey_positions.get(&target) {
+                    let distance = crate::utils::wrapped_distance(
+                        current_pos,
+                        *target_pos,
+                        &config.world_size,
+                    );
+                    let hunter_count = hunters_per_prey.get(&target).copied().unwrap_or(0);
+
+                    // Switch if too many hunters or target too far
+                    if hunter_count > config.crowding.max_hunters_per_target
+                        || distance > vision_range * 2.0
+                    {
+                        need_new_target = true;
+                    }
+                } else {
                     need_new_target = true;
                 }
             } else {
                 need_new_target = true;
             }
-        } else {
-            need_new_target = true;
-        }
 
-        // Find new target if needed using wrapped distances
-        if need_new_target {
-            hunt_target.0 = prey_positions
-                .iter()
-                .filter(|(_, pos)| {
-                    let distance =
-                        crate::utils::wrapped_distance(current_pos, **pos, &config.world_size);
-                    distance < genome.vision_range
-                })
-                .min_by_key(|(prey_entity, pos)| {
-                    let hunter_count = hunters_per_prey.get(prey_entity).copied().unwrap_or(0);
-                    let distance =
-                        crate::utils::wrapped_distance(current_pos, **pos, &config.world_size);
-                    (hunter_count * 1000) + distance as usize
-                })
-                .map(|(e, _)| *e);
-        }
+            // Find new target if needed using wrapped distances. The grid yields
+            // candidates in a fixed order, so ties resolve the same way every run.
+            if need_new_target && carcass.is_none() {
+                hunt_target.0 = grid
+                    .within_any(&diet_layers[species.0], current_pos, vision_range)
+                    .filter(|&(prey_entity, _, distance)| {
+                        distance < vision_range
+                            && worth_eating(&feedings[species.0], &foods, prey_entity, genome.size)
+                    })
+                    .min_by_key(|(prey_entity, _, distance)| {
+                        let hunter_count = hunters_per_prey.get(prey_entity).copied().unwrap_or(0);
+                        (hunter_count * 1000) + *distance as usize
+                    })
+                    .map(|(e, _, _)| e);
+            }
 
-        // Move toward target using wrapped direction
-        let mut desired_direction = if let Some(target) = hunt_target.0
-            && let Some(target_pos) = prey_positions.get(&target)
-        {
-            let to_prey =
-                crate::utils::wrapped_direction(current_pos, *target_pos, &config.world_size);
-            to_prey.normalize()
-        } else {
-            // Purposeful exploration when no target
-            // Check if we've reached the current waypoint
-            let to_waypoint =
-                crate::utils::wrapped_direction(current_pos, waypoint.target, &config.world_size);
-            let distance_to_waypoint = to_waypoint.length();
-
-            if distance_to_waypoint < waypoint.reached_threshold {
-                // Pick a new waypoint - prefer areas far from current position
-                let angle = rng.random_range(0.0..std::f32::consts::TAU);
-                let distance =
-                    rng.random_range(genome.vision_range * 0.8..genome.vision_range * 1.5);
-                waypoint.target = current_pos + Vec2::new(angle.cos(), angle.sin()) * distance;
-
-                // Wrap waypoint to world bounds
-                if waypoint.target.x > config.world_size.x / 2.0 {
-                    waypoint.target.x -= config.world_size.x;
-                } else if waypoint.target.x < -config.world_size.x / 2.0 {
-                    waypoint.target.x += config.world_size.x;
+            // Move toward target, around any obstacles in the way
+            let mut desired_direction = if let Some(carcass_pos) = carcass {
+                // Settle on the carcass to eat
+                if wrapped_distance(current_pos, carcass_pos, &config.world_size) > 5.0 {
+                    paths.heading(current_pos, carcass_pos)
+                } else {
+                    Vec2::ZERO
                 }
-                if waypoint.target.y > config.world_size.y / 2.0 {
-                    waypoint.target.y -= config.world_size.y;
-                } else if waypoint.target.y < -config.world_size.y / 2.0 {
-                    waypoint.target.y += config.world_size.y;
+            } else if let Some(target) = hunt_target.0
+                && let Some((target_pos, _)) = prey_positions.get(&target)
+            {
+                paths.heading(current_pos, *target_pos)
+            } else {
+                // Purposeful exploration when no target
+                // Check if we've reached the current waypoint
+                let to_waypoint = crate::utils::wrapped_direction(
+                    current_pos,
+                    waypoint.target,
+                    &config.world_size,
+                );
+                let distance_to_waypoint = to_waypoint.length();
+
+                if distance_to_waypoint < waypoint.reached_threshold
+                    || obstacles.contains(waypoint.target)
+                {
+                    // Pick a new waypoint - prefer areas far from current position
+                    waypoint.target =
+                        next_waypoint(current_pos, vision_range, &config.world_size, &mut rng);
                 }
-            }
 
-            // Move toward exploration waypoint
-            to_waypoint.normalize()
-        };
-
-        // Add separation from other predators (avoid crowding) using wrapped distances
-        let separation_radius = 50.0;
-        let mut separation_force = Vec2::ZERO;
-        for (_, other_pos, _) in &predator_data {
-            let to_other =
-                crate::utils::wrapped_direction(current_pos, *other_pos, &config.world_size);
-            let distance = to_other.length();
-            if distance > 0.1 && distance < separation_radius {
-                separation_force -=
-                    to_other.normalize() * (separation_radius - distance) / separation_radius;
+                // Move toward exploration waypoint
+                paths.heading(current_pos, waypoint.target)
+            };
+
+            // Add separation from other predators (avoid crowding) using wrapped distances
+            let separation_radius = config.crowding.hunter_spacing;
+            let mut separation_force = Vec2::ZERO;
+            for (_, other_pos, distance) in
+                grid.within(GridLayer::Living(species), current_pos, separation_radius)
+            {
+                let to_other =
+                    crate::utils::wrapped_direction(current_pos, other_pos, &config.world_size);
+                if distance > 0.1 && distance < separation_radius {
+                    separation_force -= to_other.normalize_or_zero()
+                        * (separation_radius - distance)
+                        / separation_radius;
+                }
             }
-        }
-        desired_direction += separation_force * 0.3;
-
-        // Apply age-based speed reduction
-        let age_multiplier = age_speed_multiplier(age.0);
-        let target_speed = genome.speed * age_multiplier;
-        velocity.0 = velocity
-            .0
-            .lerp(desired_direction.normalize() * target_speed, 0.1);
-        transform.translation += velocity.0.extend(0.0) * time.delta_secs();
-
-        // Wrap around world
-        wrap_position(&mut transform.translation, &config.world_size);
-    }
+            desired_direction += separation_force * 0.3;
+
+            desired_direction = obstacles.steer(
+                current_pos,
+                desired_direction,
+                config.obstacles.avoidance_distance,
+            );
+
+            // Apply age-based speed reduction
+            let age_multiplier = age_speed_multiplier(age.0, config.max_age);
+            let target_speed = genome.speed * biome.speed * age_multiplier;
+            velocity.0 = velocity.0.lerp(
+                desired_direction.normalize_or_zero() * target_speed,
+                steering_blend(dt),
+            );
+            transform.translation += velocity.0.extend(0.0) * dt;
+
+            // Wrap around world
+            wrap_position(&mut transform.translation, &config.world_size);
+            obstacles.keep_out(&mut transform.translation);
+        },
+    );
 }
 
+/// Scavengers head for the nearest corpse from their carrion list and
+/// explore between waypoints otherwise
+#[allow(clippy::too_many_arguments)]
 pub fn scavenger_movement_system(
     mut scavengers: ScavengerMovementQuery,
-    corpses: CorpseTargetQuery,
+    corpses: CorpseQuery,
+    grid: Res<SpatialGrid>,
+    biomes: Res<BiomeMap>,
+    obstacles: Res<Obstacles>,
+    paths: Res<FlowFields>,
+    climate: Res<Climate>,
     config: Res<SimulationConfig>,
+    tick_seed: Res<TickSeed>,
     time: Res<Time>,
 ) {
-    let mut rng = rand::rng();
+    let dt = time.delta_secs();
+    let night_vision = night_vision(&config, &climate);
 
-    // Collect corpse positions
-    let corpse_positions: std::collections::HashMap<Entity, (Vec2, f32)> = corpses
+    // For each species, the corpses it eats and how much biomass they must hold
+    let registry = &config.species;
+    let carrion: Vec<Vec<(SpeciesId, f32)>> = registry
         .iter()
-        .map(|(e, t, energy)| (e, (t.translation.xy(), energy.0)))
+        .map(|(species, _)| {
+            config
+                .food_web
+                .feedings(registry, species)
+                .into_iter()
+                .filter(|feeding| feeding.mode == FeedingMode::Scavenge)
+                .map(|feeding| (feeding.resource, feeding.min_energy))
+                .collect()
+        })
         .collect();
-
-    // Collect scavenger data for separation calculations
-    let scavenger_data: Vec<(Entity, Vec2)> = scavengers
+    let carrion_layers: Vec<Vec<GridLayer>> = carrion
         .iter()
-        .map(|(e, t, _, _, _, _)| (e, t.translation.xy()))
+        .map(|foods| {
+            foods
+                .iter()
+                .map(|&(food, _)| GridLayer::Corpse(food))
+                .collect()
+        })
         .collect();
 
-    for (_scavenger_entity, mut transform, mut velocity, mut waypoint, genome, age) in
-        scavengers.iter_mut()
-    {
-        let current_pos = transform.translation.xy();
+    scavengers.par_iter_mut().for_each(
+        |(entity, mut transform, mut velocity, mut waypoint, genome, age, &species)| {
+            let mut rng = tick_seed.entity_rng(SCAVENGER_STREAM, entity);
+            let current_pos = transform.translation.xy();
+            let biome = config.terrain.biome(biomes.at(current_pos));
+            let vision_range = genome.vision_range * biome.visibility * night_vision[species.0];
 
-        // Look for nearby corpses
-        let nearest_corpse = corpse_positions
-            .iter()
-            .filter(|(_, (_, energy))| *energy > 10.0) // Only consider corpses with enough energy
-            .min_by_key(|(_, (pos, _))| {
-                crate::utils::wrapped_distance(current_pos, *pos, &config.world_size) as i32
-            });
-
-        let mut desired_direction = if let Some((_, (corpse_pos, _))) = nearest_corpse {
-            let distance =
-                crate::utils::wrapped_distance(current_pos, *corpse_pos, &config.world_size);
-            if distance < genome.vision_range {
+            // Look for nearby corpses with enough biomass left to be worth it
+            let nearest_corpse = grid.nearest(
+                &carrion_layers[species.0],
+                current_pos,
+                vision_range,
+                |corpse| {
+                    corpses.get(corpse).is_ok_and(|corpse| {
+                        carrion[species.0].iter().any(|&(food, minimum)| {
+                            food == corpse.species && corpse.biomass > minimum
+                        })
+                    })
+                },
+            );
+
+            let mut desired_direction = match nearest_corpse {
                 // Move toward corpse
-                crate::utils::wrapped_direction(current_pos, *corpse_pos, &config.world_size)
-                    .normalize()
-            } else {
+                Some((_, corpse_pos, distance)) if distance < vision_range => {
+                    paths.heading(current_pos, corpse_pos)
+                }
                 // No corpse in vision range, explore
-                Vec2::ZERO
+                _ => Vec2::ZERO,
+            };
+
+            // If no corpse in sight, use exploration behavior
+            if desired_direction.length() < 0.1 {
+                let to_waypoint = crate::utils::wrapped_direction(
+                    current_pos,
+                    waypoint.target,
+                    &config.world_size,
+                );
+                let distance_to_waypoint = to_waypoint.length();
+
+                if distance_to_waypoint < waypoint.reached_threshold
+                    || obstacles.contains(waypoint.target)
+                {
+                    // Pick a new waypoint
+                    waypoint.target =
+                        next_waypoint(current_pos, vision_range, &config.world_size, &mut rng);
+                }
+
+                desired_direction = paths.heading(current_pos, waypoint.target);
             }
-        } else {
-            Vec2::ZERO
-        };
-
-        // If no corpse in sight, use exploration behavior
-        if desired_direction.length() < 0.1 {
-            let to_waypoint =
-                crate::utils::wrapped_direction(current_pos, waypoint.target, &config.world_size);
-            let distance_to_waypoint = to_waypoint.length();
-
-            if distance_to_waypoint < waypoint.reached_threshold {
-                // Pick a new waypoint
-                let angle = rng.random_range(0.0..std::f32::consts::TAU);
-                let distance =
-                    rng.random_range(genome.vision_range * 0.8..genome.vision_range * 1.5);
-                waypoint.target = current_pos + Vec2::new(angle.cos(), angle.sin()) * distance;
-
-                // Wrap waypoint to world bounds
-                if waypoint.target.x > config.world_size.x / 2.0 {
-                    waypoint.target.x -= config.world_size.x;
-                } else if waypoint.target.x < -config.world_size.x / 2.0 {
-                    waypoint.target.x += config.world_size.x;
+
+            // Add separation from other scavengers
+            let separation_radius = config.crowding.scavenger_spacing;
+            let mut separation_force = Vec2::ZERO;
+            for (_, other_pos, distance) in
+                grid.within(GridLayer::Living(species), current_pos, separation_radius)
+            {
+                let to_other =
+                    crate::utils::wrapped_direction(current_pos, other_pos, &config.world_size);
+                if distance > 0.1 && distance < separation_radius {
+                    separation_force -= to_other.normalize_or_zero()
+                        * (separation_radius - distance)
+                        / separation_radius;
                 }
-                if waypoint.target.y > config.world_size.y / 2.0 {
-                    waypoint.target.y -= config.world_size.y;
-                } else if waypoint.target.y < -config.world_size.y / 2.0 {
-                    waypoint.target.y += config.world_size.y;
+            }
+            desired_direction += separation_force * 0.3;
+
+            desired_direction = obstacles.steer(
+                current_pos,
+                desired_direction,
+                config.obstacles.avoidance_distance,
+            );
+
+            // Apply age-based speed reduction
+            let age_multiplier = age_speed_multiplier(age.0, config.max_age);
+            let target_speed = genome.speed * biome.speed * age_multiplier;
+            velocity.0 = velocity.0.lerp(
+                desired_direction.normalize_or_zero() * target_speed,
+                steering_blend(dt),
+            );
+            transform.translation += velocity.0.extend(0.0) * dt;
+
+            // Wrap around world
+            wrap_position(&mut transform.translation, &config.world_size);
+            obstacles.keep_out(&mut transform.translation);
+        },
+    );
+}
+
+/// Omnivores weigh three drives by hunger: well fed, they graze and give
+/// hunters a wide berth; hungry, they chase prey smaller than themselves
+/// and only flee hunters that come close
+#[allow(clippy::too_many_arguments)]
+pub fn omnivore_movement_system(
+    mut omnivores: OmnivoreMovementQuery,
+    foods: FoodQuery,
+    grid: Res<SpatialGrid>,
+    biomes: Res<BiomeMap>,
+    obstacles: Res<Obstacles>,
+    paths: Res<FlowFields>,
+    climate: Res<Climate>,
+    config: Res<SimulationConfig>,
+    tick_seed: Res<TickSeed>,
+    time: Res<Time>,
+) {
+    let dt = time.delta_secs();
+    let night_vision = night_vision(&config, &climate);
+
+    // For each species, what it flees, chases and grazes
+    let registry = &config.species;
+    let mut threats = Vec::new();
+    let mut chases = Vec::new();
+    let mut grazes = Vec::new();
+    for (species, _) in registry.iter() {
+        let feedings = config.food_web.feedings(registry, species);
+        threats.push(
+            config
+                .food_web
+                .threats(registry, species)
+                .into_iter()
+                .map(GridLayer::Living)
+                .collect::<Vec<_>>(),
+        );
+        chases.push(
+            feedings
+                .iter()
+                .filter(|feeding| feeding.mode == FeedingMode::Kill)
+                .copied()
+                .collect::<Vec<_>>(),
+        );
+        grazes.push(
+            feedings
+                .iter()
+                .filter(|feeding| feeding.mode == FeedingMode::Graze)
+                .copied()
+                .collect::<Vec<_>>(),
+        );
+    }
+    let layers = |feedings: &Vec<Vec<Feeding>>| -> Vec<Vec<GridLayer>> {
+        feedings
+            .iter()
+            .map(|feedings| {
+                feedings
+                    .iter()
+                    .map(|feeding| GridLayer::Living(feeding.resource))
+                    .collect()
+            })
+            .collect()
+    };
+    let chase_layers = layers(&chases);
+    let graze_layers = layers(&grazes);
+
+    omnivores.par_iter_mut().for_each(
+        |(entity, mut transform, mut velocity, mut waypoint, genome, energy, age, &species)| {
+            let mut rng = tick_seed.entity_rng(OMNIVORE_STREAM, entity);
+            let current_pos = transform.translation.xy();
+            let biome = config.terrain.biome(biomes.at(current_pos));
+            let vision_range = genome.vision_range * biome.visibility * night_vision[species.0];
+            let hunger = (1.0 - energy.0 / genome.reproduction_threshold).clamp(0.0, 1.0);
+            let mut desired_direction = Vec2::ZERO;
+
+            // Flee hunters; the hungrier, the closer they may come
+            let flee_radius = vision_range * (1.2 - 0.6 * hunger);
+            let mut threat_level: f32 = 0.0;
+            for (_, threat_pos, distance) in
+                grid.within_any(&threats[species.0], current_pos, flee_radius)
+            {
+                if distance < flee_radius {
+                    let to_threat = crate::utils::wrapped_direction(
+                        current_pos,
+                        threat_pos,
+                        &config.world_size,
+                    );
+                    let flee_strength = (flee_radius - distance) / flee_radius;
+                    desired_direction -= to_threat.normalize_or_zero() * flee_strength * 2.0;
+                    threat_level = threat_level.max(flee_strength);
                 }
             }
 
-            desired_direction = to_waypoint.normalize();
-        }
+            // Chase small prey once hungry enough to be worth the effort
+            let mut chasing = false;
+            if hunger > 0.3
+                && threat_level < 0.5
+                && let Some((_, prey_pos, distance)) = grid.nearest(
+                    &chase_layers[species.0],
+                    current_pos,
+                    vision_range,
+                    |target| worth_eating(&chases[species.0], &foods, target, genome.size),
+                )
+                && distance < vision_range
+            {
+                desired_direction += paths.heading(current_pos, prey_pos) * hunger * 1.5;
+                chasing = true;
+            }
 
-        // Add separation from other scavengers
-        let separation_radius = 40.0;
-        let mut separation_force = Vec2::ZERO;
-        for (_, other_pos) in &scavenger_data {
-            let to_other =
-                crate::utils::wrapped_direction(current_pos, *other_pos, &config.world_size);
-            let distance = to_other.length();
-            if distance > 0.1 && distance < separation_radius {
-                separation_force -=
-                    to_other.normalize() * (separation_radius - distance) / separation_radius;
+            // Otherwise graze, which a well-fed omnivore prefers
+            if !chasing
+                && threat_level < 0.5
+                && let Some((_, plant_pos, distance)) = grid.nearest(
+                    &graze_layers[species.0],
+                    current_pos,
+                    vision_range,
+                    |plant| worth_eating(&grazes[species.0], &foods, plant, genome.size),
+                )
+                && distance < vision_range
+            {
+                desired_direction += paths.heading(current_pos, plant_pos) * (1.0 - 0.5 * hunger);
             }
-        }
-        desired_direction += separation_force * 0.3;
-
-        // Apply age-based speed reduction
-        let age_multiplier = age_speed_multiplier(age.0);
-        let target_speed = genome.speed * age_multiplier;
-        velocity.0 = velocity
-            .0
-            .lerp(desired_direction.normalize() * target_speed, 0.1);
-        transform.translation += velocity.0.extend(0.0) * time.delta_secs();
-
-        // Wrap around world
-        wrap_position(&mut transform.translation, &config.world_size);
-    }
+
+            // Explore when nothing is in sight
+            if desired_direction.length() < 0.1 {
+                let to_waypoint = crate::utils::wrapped_direction(
+                    current_pos,
+                    waypoint.target,
+                    &config.world_size,
+                );
+                if to_waypoint.length() < waypoint.reached_threshold
+                    || obstacles.contains(waypoint.target)
+                {
+                    waypoint.target =
+                        next_waypoint(current_pos, vision_range, &config.world_size, &mut rng);
+                }
+                desired_direction = paths.heading(current_pos, waypoint.target);
+            }
+
+            desired_direction = obstacles.steer(
+                current_pos,
+                desired_direction,
+                config.obstacles.avoidance_distance,
+            );
+
+            // A short burst of speed while chasing
+            let speed_multiplier = if chasing { 1.2 } else { 1.0 };
+            let age_multiplier = age_speed_multiplier(age.0, config.max_age);
+            let target_speed = genome.speed * biome.speed * speed_multiplier * age_multiplier;
+            velocity.0 = velocity.0.lerp(
+                desired_direction.normalize_or_zero() * target_speed,
+                steering_blend(dt),
+            );
+            transform.translation += velocity.0.extend(0.0) * dt;
+
+            // Wrap around world
+            wrap_position(&mut transform.translation, &config.world_size);
+            obstacles.keep_out(&mut transform.translation);
+        },
+    );
 }
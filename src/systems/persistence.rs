@@ -0,0 +1,61 @@
+use bevy::prelude::*;
+
+use crate::phylogeny::BirthDeathLedger;
+use crate::resources::*;
+use crate::save::*;
+
+// ===== SAVE / LOAD SYSTEMS =====
+
+/// Replace the world with a pending save, if one was requested
+pub fn apply_pending_world_load(world: &mut World) {
+    if let Some(PendingWorldLoad(save)) = world.remove_resource::<PendingWorldLoad>() {
+        save.restore(world);
+        info!("Loaded world with {} organisms", save.organisms.len());
+    }
+}
+
+/// F5 saves the world to the save path, F9 loads it back
+pub fn save_load_hotkeys_system(
+    mut commands: Commands,
+    keys: Res<ButtonInput<KeyCode>>,
+    save_path: Res<SavePath>,
+) {
+    if keys.just_pressed(KeyCode::F5) {
+        let path = save_path.0.clone();
+        commands.queue(
+            move |world: &mut World| match SaveFile::capture(world).write(&path) {
+                Ok(()) => info!("Saved world to {}", path.display()),
+                Err(err) => error!("{err}"),
+            },
+        );
+    }
+
+    if keys.just_pressed(KeyCode::F9) {
+        match SaveFile::read(&save_path.0) {
+            Ok(save) => commands.insert_resource(PendingWorldLoad(save)),
+            Err(err) => error!("{err}"),
+        }
+    }
+}
+
+/// N writes the run's phylogenetic tree as Newick
+pub fn newick_export_hotkey_system(
+    keys: Res<ButtonInput<KeyCode>>,
+    ledger: Res<BirthDeathLedger>,
+    newick_path: Res<NewickPath>,
+    config: Res<SimulationConfig>,
+) {
+    if keys.just_pressed(KeyCode::KeyN) {
+        match ledger.write_newick(&newick_path.0, &config.species) {
+            Ok(()) => info!(
+                "Wrote phylogeny of {} organisms to {}",
+                ledger.entries.len(),
+                newick_path.0.display()
+            ),
+            Err(err) => error!(
+                "could not write phylogeny '{}': {err}",
+                newick_path.0.display()
+            ),
+        }
+    }
+}
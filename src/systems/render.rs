@@ -0,0 +1,233 @@
+use bevy::prelude::*;
+
+use bevy::asset::RenderAssetUsages;
+use bevy::image::ImageSampler;
+use bevy::render::render_resource::{Extent3d, TextureDimension, TextureFormat};
+
+use crate::components::*;
+use crate::obstacles::{ObstacleKind, Obstacles};
+use crate::resources::*;
+use crate::systems::input::CameraController;
+use crate::terrain::BiomeMap;
+
+// ===== RENDER SETUP =====
+
+pub fn setup_view(mut commands: Commands) {
+    commands.spawn((
+        Camera2d,
+        Projection::Orthographic(OrthographicProjection {
+            scale: 4.0,
+            ..OrthographicProjection::default_2d()
+        }),
+        CameraController::default(),
+    ));
+
+    // Spawn UI text
+    commands.spawn((
+        Text::new("Population Stats"),
+        TextFont {
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+    ));
+}
+
+// ===== TERRAIN BACKGROUND =====
+
+/// The biome map drawn under everything else
+#[derive(Component)]
+pub struct TerrainBackground;
+
+/// Draw the biome map as one image, one pixel per cell, redrawn whenever the
+/// map is replaced (e.g. by loading a save) or biome colours change
+pub fn terrain_background_system(
+    mut commands: Commands,
+    biomes: Res<BiomeMap>,
+    config: Res<SimulationConfig>,
+    backgrounds: Query<Entity, With<TerrainBackground>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if !biomes.is_changed() && !config.is_changed() {
+        return;
+    }
+    for background in backgrounds.iter() {
+        commands.entity(background).despawn();
+    }
+
+    // Image rows run top to bottom, map rows bottom to top
+    let (columns, rows) = biomes.dimensions();
+    let data: Vec<u8> = biomes
+        .cells()
+        .chunks(columns.max(1))
+        .rev()
+        .flatten()
+        .flat_map(|&biome| {
+            srgb(config.terrain.biome(biome).color)
+                .to_srgba()
+                .to_u8_array()
+        })
+        .collect();
+    if data.is_empty() {
+        return;
+    }
+    let mut image = Image::new(
+        Extent3d {
+            width: columns as u32,
+            height: rows as u32,
+            depth_or_array_layers: 1,
+        },
+        TextureDimension::D2,
+        data,
+        TextureFormat::Rgba8UnormSrgb,
+        RenderAssetUsages::RENDER_WORLD,
+    );
+    image.sampler = ImageSampler::nearest();
+
+    commands.spawn((
+        TerrainBackground,
+        Sprite {
+            image: images.add(image),
+            custom_size: Some(config.world_size),
+            ..default()
+        },
+        Transform::from_xyz(0.0, 0.0, -10.0),
+    ));
+}
+
+/// A drawn rock or lake
+#[derive(Component)]
+pub struct ObstacleShape;
+
+/// Draw each obstacle as a disc above the terrain, redrawn whenever the
+/// obstacles are replaced (e.g. by loading a save). Discs crossing the
+/// world edge are drawn again on the opposite side.
+pub fn obstacle_shape_system(
+    mut commands: Commands,
+    obstacles: Res<Obstacles>,
+    config: Res<SimulationConfig>,
+    shapes: Query<Entity, With<ObstacleShape>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if !obstacles.is_changed() {
+        return;
+    }
+    for shape in shapes.iter() {
+        commands.entity(shape).despawn();
+    }
+
+    let lake = materials.add(Color::srgb(0.12, 0.28, 0.5));
+    let rock = materials.add(Color::srgb(0.36, 0.34, 0.32));
+    for obstacle in obstacles.iter() {
+        let material = match obstacle.kind {
+            ObstacleKind::Lake => lake.clone(),
+            ObstacleKind::Rock => rock.clone(),
+        };
+        let mesh = meshes.add(Circle::new(obstacle.radius));
+        let half = config.world_size / 2.0;
+        for x in -1..=1 {
+            for y in -1..=1 {
+                let center = obstacle.center + Vec2::new(x as f32, y as f32) * config.world_size;
+                let reach = center.abs() - obstacle.radius;
+                if reach.x < half.x && reach.y < half.y {
+                    commands.spawn((
+                        ObstacleShape,
+                        Mesh2d(mesh.clone()),
+                        MeshMaterial2d(material.clone()),
+                        Transform::from_translation(center.extend(-5.0)),
+                    ));
+                }
+            }
+        }
+    }
+}
+
+/// The night tint laid over the whole world
+#[derive(Component)]
+pub struct NightShade;
+
+/// Shade the scene with the night tint, the more opaque the darker it is
+pub fn night_shade_system(
+    mut commands: Commands,
+    climate: Res<Climate>,
+    config: Res<SimulationConfig>,
+    mut shades: Query<&mut Sprite, With<NightShade>>,
+) {
+    let color = srgb(config.night.tint).with_alpha(config.night.tint_strength * climate.darkness());
+    if let Ok(mut sprite) = shades.single_mut() {
+        sprite.color = color;
+        sprite.custom_size = Some(config.world_size);
+    } else {
+        commands.spawn((
+            NightShade,
+            Sprite {
+                color,
+                custom_size: Some(config.world_size),
+                ..default()
+            },
+            Transform::from_xyz(0.0, 0.0, 10.0),
+        ));
+    }
+}
+
+// ===== SPRITE SYSTEMS =====
+
+/// Give newly spawned organisms a sprite. The simulation itself never touches
+/// sprites, so headless runs carry no rendering data at all.
+pub fn attach_sprites_system(
+    mut commands: Commands,
+    organisms: Query<(Entity, &SpeciesId), Without<Sprite>>,
+    config: Res<SimulationConfig>,
+) {
+    for (entity, &species) in organisms.iter() {
+        let species_config = config.species.get(species);
+        commands.entity(entity).insert(organism_sprite(
+            srgb(species_config.color),
+            species_config.sprite_size,
+        ));
+    }
+}
+
+/// Swap an organism's sprite for its corpse sprite when it dies
+pub fn corpse_sprite_system(
+    mut commands: Commands,
+    corpses: Query<(Entity, &Corpse), Added<Corpse>>,
+    config: Res<SimulationConfig>,
+) {
+    for (entity, corpse) in corpses.iter() {
+        let species_config = config.species.get(corpse.species);
+        commands.entity(entity).insert(organism_sprite(
+            srgb(species_config.corpse_color),
+            species_config.sprite_size,
+        ));
+    }
+}
+
+/// Gradually fade out corpses as they decay
+pub fn corpse_fade_system(mut corpses: Query<(&Corpse, &mut Sprite)>) {
+    for (corpse, mut sprite) in corpses.iter_mut() {
+        let decay_progress = corpse.decay_timer / corpse.max_decay_time;
+        let alpha = decay_progress.max(0.2); // Keep minimum alpha of 0.2
+        sprite.color = sprite.color.with_alpha(alpha);
+    }
+}
+
+/// A color from a species definition's `(r, g, b)`
+pub fn srgb((red, green, blue): (f32, f32, f32)) -> Color {
+    Color::srgb(red, green, blue)
+}
+
+fn organism_sprite(color: Color, size: f32) -> Sprite {
+    Sprite {
+        color,
+        custom_size: Some(Vec2::splat(size)),
+        ..default()
+    }
+}
@@ -1,142 +1,31 @@
 use bevy::prelude::*;
-use rand::Rng;
 
-use crate::components::*;
+use crate::obstacles::Obstacles;
 use crate::resources::*;
-use crate::systems::input::CameraController;
+use crate::species::spawn_founder;
 
 // ===== SETUP SYSTEM =====
 
-pub fn setup(mut commands: Commands, config: Res<SimulationConfig>) {
-    commands.spawn((
-        Camera2d,
-        Projection::Orthographic(OrthographicProjection {
-            scale: 4.0,
-            ..OrthographicProjection::default_2d()
-        }),
-        CameraController::default(),
-    ));
-
-    let mut rng = rand::rng();
-
-    // Spawn plants
-    for _ in 0..config.initial_plants {
-        let x = rng.random_range(-config.world_size.x / 2.0..config.world_size.x / 2.0);
-        let y = rng.random_range(-config.world_size.y / 2.0..config.world_size.y / 2.0);
-
-        commands.spawn((
-            Plant,
-            Genome::random_plant(),
-            Energy(rng.random_range(20.0..50.0)),
-            Age(0.0),
-            Transform::from_xyz(x, y, 0.0),
-            Sprite {
-                color: Color::srgb(0.2, 0.8, 0.2),
-                custom_size: Some(Vec2::splat(8.0)),
-                ..default()
-            },
-        ));
-    }
-
-    // Spawn prey
-    for _ in 0..config.initial_prey {
-        let x = rng.random_range(-config.world_size.x / 2.0..config.world_size.x / 2.0);
-        let y = rng.random_range(-config.world_size.y / 2.0..config.world_size.y / 2.0);
-
-        commands.spawn((
-            Prey,
-            Genome::random_prey(),
-            Energy(rng.random_range(40.0..80.0)),
-            Age(0.0),
-            Velocity(Vec2::ZERO),
-            Stamina::default(),
-            Transform::from_xyz(x, y, 1.0),
-            Sprite {
-                color: Color::srgb(0.3, 0.3, 0.9),
-                custom_size: Some(Vec2::splat(12.0)),
-                ..default()
-            },
-        ));
+pub fn setup(
+    mut commands: Commands,
+    config: Res<SimulationConfig>,
+    obstacles: Res<Obstacles>,
+    mut rng: ResMut<SimulationRng>,
+    mut lineage: ResMut<LineageTracker>,
+    clock: Res<SimulationClock>,
+) {
+    // Each species' founders, in registry order
+    for (species, species_config) in config.species.iter() {
+        for _ in 0..species_config.initial_count {
+            let position = obstacles.random_open_position(&mut *rng);
+            spawn_founder(
+                &mut commands,
+                &config.species,
+                species,
+                position,
+                lineage.founder(clock.elapsed),
+                &mut *rng,
+            );
+        }
     }
-
-    // Spawn predators
-    for _ in 0..config.initial_predators {
-        let x = rng.random_range(-config.world_size.x / 2.0..config.world_size.x / 2.0);
-        let y = rng.random_range(-config.world_size.y / 2.0..config.world_size.y / 2.0);
-
-        // Generate initial exploration waypoint
-        let waypoint_angle = rng.random_range(0.0..std::f32::consts::TAU);
-        let waypoint_distance = rng.random_range(100.0..200.0);
-        let waypoint_target = Vec2::new(
-            x + waypoint_angle.cos() * waypoint_distance,
-            y + waypoint_angle.sin() * waypoint_distance,
-        );
-
-        commands.spawn((
-            Predator,
-            Genome::random_predator(),
-            Energy(rng.random_range(60.0..100.0)),
-            Age(0.0),
-            Velocity(Vec2::ZERO),
-            HuntTarget(None),
-            ExplorationWaypoint {
-                target: waypoint_target,
-                reached_threshold: 30.0,
-            },
-            Transform::from_xyz(x, y, 2.0),
-            Sprite {
-                color: Color::srgb(0.9, 0.2, 0.2),
-                custom_size: Some(Vec2::splat(16.0)),
-                ..default()
-            },
-        ));
-    }
-
-    // Spawn scavengers
-    for _ in 0..config.initial_scavengers {
-        let x = rng.random_range(-config.world_size.x / 2.0..config.world_size.x / 2.0);
-        let y = rng.random_range(-config.world_size.y / 2.0..config.world_size.y / 2.0);
-
-        // Generate initial exploration waypoint
-        let waypoint_angle = rng.random_range(0.0..std::f32::consts::TAU);
-        let waypoint_distance = rng.random_range(100.0..200.0);
-        let waypoint_target = Vec2::new(
-            x + waypoint_angle.cos() * waypoint_distance,
-            y + waypoint_angle.sin() * waypoint_distance,
-        );
-
-        commands.spawn((
-            Scavenger,
-            Genome::random_scavenger(),
-            Energy(rng.random_range(50.0..80.0)),
-            Age(0.0),
-            Velocity(Vec2::ZERO),
-            ExplorationWaypoint {
-                target: waypoint_target,
-                reached_threshold: 30.0,
-            },
-            Transform::from_xyz(x, y, 1.5),
-            Sprite {
-                color: Color::srgb(0.7, 0.5, 0.2), // Brown color for scavengers
-                custom_size: Some(Vec2::splat(14.0)),
-                ..default()
-            },
-        ));
-    }
-
-    // Spawn UI text
-    commands.spawn((
-        Text::new("Population Stats"),
-        TextFont {
-            font_size: 20.0,
-            ..default()
-        },
-        TextColor(Color::WHITE),
-        Node {
-            position_type: PositionType::Absolute,
-            top: Val::Px(10.0),
-            left: Val::Px(10.0),
-            ..default()
-        },
-    ));
 }
@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+
+use crate::components::*;
+use crate::obstacles::Obstacles;
+use crate::pathfinding::FlowFields;
+use crate::resources::*;
+use crate::spatial::{GridLayer, SpatialGrid};
+
+// ===== QUERY TYPE ALIASES =====
+
+type GridEntryQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static Transform,
+        Option<&'static SpeciesId>,
+        Option<&'static Corpse>,
+    ),
+    With<Genome>,
+>;
+
+// ===== SPATIAL GRID SYSTEMS =====
+
+pub fn rebuild_spatial_grid_system(
+    mut grid: ResMut<SpatialGrid>,
+    entries: GridEntryQuery,
+    config: Res<SimulationConfig>,
+) {
+    grid.reset(
+        config.world_size,
+        config.grid_cell_size,
+        config.species.len(),
+    );
+
+    for (entity, transform, species, corpse) in entries.iter() {
+        let layer = match (species, corpse) {
+            (_, Some(corpse)) => GridLayer::Corpse(corpse.species),
+            (Some(&species), None) => GridLayer::Living(species),
+            (None, None) => continue,
+        };
+        grid.insert(layer, entity, transform.translation.xy());
+    }
+}
+
+/// Rebuild the pathfinding grid when the obstacles are replaced (e.g. by
+/// loading a save) or its settings change
+pub fn sync_flow_fields_system(
+    mut fields: ResMut<FlowFields>,
+    obstacles: Res<Obstacles>,
+    config: Res<SimulationConfig>,
+) {
+    if !fields.matches(&obstacles, &config.pathfinding) {
+        *fields = FlowFields::new(&obstacles, &config.pathfinding);
+    }
+}
@@ -1,29 +1,48 @@
 use bevy::prelude::*;
 
 use crate::components::*;
+use crate::phylogeny::BirthDeathLedger;
 use crate::resources::*;
+use crate::soil::SoilNutrients;
+
+// ===== QUERY TYPE ALIASES =====
+
+type HistoryQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static SpeciesId,
+        &'static Energy,
+        &'static Age,
+        &'static Genome,
+        Option<&'static Lineage>,
+    ),
+>;
 
 // ===== STATS SYSTEMS =====
 
 pub fn update_population_stats(
-    plants: Query<(), With<Plant>>,
-    prey: Query<(), With<Prey>>,
-    predators: Query<(), With<Predator>>,
-    scavengers: Query<(), With<Scavenger>>,
+    organisms: Query<&SpeciesId>,
+    config: Res<SimulationConfig>,
     mut stats: ResMut<PopulationStats>,
 ) {
-    stats.plants = plants.iter().count();
-    stats.prey = prey.iter().count();
-    stats.predators = predators.iter().count();
-    stats.scavengers = scavengers.iter().count();
+    let mut counts = vec![0; config.species.len()];
+    for species in organisms.iter() {
+        counts[species.0] += 1;
+    }
+
+    stats.species = config
+        .species
+        .iter()
+        .map(|(species, species_config)| (species_config.name.clone(), counts[species.0]))
+        .collect();
 }
 
 pub fn record_history_system(
     mut history: ResMut<SimulationHistory>,
-    stats: Res<PopulationStats>,
-    plants: Query<(&Energy, &Age), With<Plant>>,
-    prey: Query<(&Energy, &Age, &Genome), With<Prey>>,
-    predators: Query<(&Energy, &Age, &Genome), With<Predator>>,
+    organisms: HistoryQuery,
+    soil: Res<SoilNutrients>,
+    config: Res<SimulationConfig>,
     time: Res<Time>,
 ) {
     history.time_since_last_record += time.delta_secs();
@@ -31,68 +50,79 @@ pub fn record_history_system(
     if history.time_since_last_record >= history.record_interval {
         history.time_since_last_record = 0.0;
 
-        // Calculate total energy
         let mut total_energy = 0.0;
-        for (energy, _) in plants.iter() {
-            total_energy += energy.0;
-        }
-        for (energy, _, _) in prey.iter() {
-            total_energy += energy.0;
-        }
-        for (energy, _, _) in predators.iter() {
+        let mut species: Vec<SpeciesSnapshot> = config
+            .species
+            .iter()
+            .map(|(_, species_config)| SpeciesSnapshot {
+                name: species_config.name.clone(),
+                ..default()
+            })
+            .collect();
+
+        // Sum energy, ages and speeds; averaged below
+        for (&id, energy, age, genome, _) in organisms.iter() {
             total_energy += energy.0;
+            let snapshot = &mut species[id.0];
+            snapshot.count += 1;
+            snapshot.avg_age += age.0;
+            snapshot.avg_speed += genome.speed;
         }
 
-        // Calculate average ages
-        let avg_plant_age = if stats.plants > 0 {
-            plants.iter().map(|(_, age)| age.0).sum::<f32>() / stats.plants as f32
-        } else {
-            0.0
-        };
-
-        let avg_prey_age = if stats.prey > 0 {
-            prey.iter().map(|(_, age, _)| age.0).sum::<f32>() / stats.prey as f32
-        } else {
-            0.0
-        };
-
-        let avg_predator_age = if stats.predators > 0 {
-            predators.iter().map(|(_, age, _)| age.0).sum::<f32>() / stats.predators as f32
-        } else {
-            0.0
-        };
-
-        // Calculate average speeds
-        let avg_prey_speed = if stats.prey > 0 {
-            prey.iter().map(|(_, _, genome)| genome.speed).sum::<f32>() / stats.prey as f32
-        } else {
-            0.0
-        };
-
-        let avg_predator_speed = if stats.predators > 0 {
-            predators
-                .iter()
-                .map(|(_, _, genome)| genome.speed)
-                .sum::<f32>()
-                / stats.predators as f32
-        } else {
-            0.0
-        };
+        for (index, snapshot) in species.iter_mut().enumerate() {
+            if snapshot.count > 0 {
+                snapshot.avg_age /= snapshot.count as f32;
+                snapshot.avg_speed /= snapshot.count as f32;
+            }
+            snapshot.generations = GenerationStats::from_lineages(
+                organisms
+                    .iter()
+                    .filter(|(id, ..)| id.0 == index)
+                    .filter_map(|(.., lineage)| lineage),
+            );
+        }
 
         let elapsed_time = history.snapshots.len() as f32 * history.record_interval;
 
         history.snapshots.push(SimulationSnapshot {
             _time: elapsed_time,
-            plant_count: stats.plants,
-            prey_count: stats.prey,
-            predator_count: stats.predators,
-            scavenger_count: stats.scavengers,
             total_energy,
-            avg_plant_age,
-            avg_prey_age,
-            avg_predator_age,
-            avg_prey_speed,
-            avg_predator_speed,
+            soil_nutrients: soil.total(),
+            species,
         });
     }
 }
+
+// ===== LEDGER OBSERVERS =====
+// Observers rather than systems, so organisms that are born and eaten within
+// the same tick are still recorded
+
+pub fn record_birth_observer(
+    add: On<Add, Lineage>,
+    newborns: Query<(&Lineage, &Genome, &SpeciesId)>,
+    mut ledger: ResMut<BirthDeathLedger>,
+) {
+    // A corpse restored from a save has no species and is already in the saved ledger
+    let Ok((lineage, genome, &species)) = newborns.get(add.entity) else {
+        return;
+    };
+    ledger.record_birth(add.entity, lineage, species, genome);
+}
+
+/// An organism dies when it becomes a corpse...
+pub fn record_corpse_observer(
+    add: On<Add, Corpse>,
+    mut ledger: ResMut<BirthDeathLedger>,
+    clock: Res<SimulationClock>,
+) {
+    ledger.record_death(add.entity, clock.elapsed);
+}
+
+/// ...or when it is despawned outright (e.g. eaten)
+pub fn record_despawn_observer(
+    remove: On<Remove, Lineage>,
+    mut ledger: ResMut<BirthDeathLedger>,
+    clock: Res<SimulationClock>,
+) {
+    ledger.record_death(remove.entity, clock.elapsed);
+}
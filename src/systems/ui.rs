@@ -2,33 +2,20 @@ use bevy::prelude::*;
 
 use crate::components::*;
 use crate::resources::*;
+use crate::systems::render::srgb;
 
 // ===== QUERY TYPE ALIASES =====
 
-type VisualPolishPlantQuery<'w, 's> = Query<
-    'w,
-    's,
-    (&'static mut Sprite, &'static Genome, &'static Energy),
-    (With<Plant>, Without<Prey>, Without<Predator>),
->;
-
-type VisualPolishPreyQuery<'w, 's> = Query<
+type VisualPolishQuery<'w, 's> = Query<
     'w,
     's,
     (
         &'static mut Sprite,
         &'static Genome,
         &'static Energy,
-        &'static Stamina,
+        &'static SpeciesId,
+        Option<&'static Stamina>,
     ),
-    (With<Prey>, Without<Plant>, Without<Predator>),
->;
-
-type VisualPolishPredatorQuery<'w, 's> = Query<
-    'w,
-    's,
-    (&'static mut Sprite, &'static Genome, &'static Energy),
-    (With<Predator>, Without<Prey>, Without<Plant>),
 >;
 
 // ===== UI SYSTEMS =====
@@ -38,6 +25,7 @@ pub fn console_output_system(
     stats: Res<PopulationStats>,
     history: Res<SimulationHistory>,
     sunlight: Res<SunlightLevel>,
+    climate: Res<Climate>,
     time: Res<Time>,
 ) {
     console.time_since_last_print += time.delta_secs();
@@ -48,69 +36,126 @@ pub fn console_output_system(
         let elapsed = history.snapshots.len() as f32 * history.record_interval;
 
         println!("\n========== Simulation Stats ({elapsed:.1}s) ==========");
-        println!(
-            "Population: Plants={}, Prey={}, Predators={}, Scavengers={}",
-            stats.plants, stats.prey, stats.predators, stats.scavengers
-        );
+        println!("Population: {}", population_line(&stats));
 
         if let Some(latest) = history.snapshots.last() {
             println!("Total Energy: {:.1}", latest.total_energy);
-            println!(
-                "Avg Ages: Plants={:.1}s, Prey={:.1}s, Predators={:.1}s",
-                latest.avg_plant_age, latest.avg_prey_age, latest.avg_predator_age
-            );
-            println!(
-                "Avg Speeds: Prey={:.1}, Predators={:.1}",
-                latest.avg_prey_speed, latest.avg_predator_speed
-            );
+            let ages: Vec<String> = latest
+                .species
+                .iter()
+                .map(|s| format!("{}={:.1}s", s.name, s.avg_age))
+                .collect();
+            println!("Avg Ages: {}", ages.join(", "));
+            let speeds: Vec<String> = latest
+                .species
+                .iter()
+                .filter(|s| s.avg_speed > 0.0)
+                .map(|s| format!("{}={:.1}", s.name, s.avg_speed))
+                .collect();
+            println!("Avg Speeds: {}", speeds.join(", "));
         }
 
         println!("Sunlight: {:.0}%", sunlight.intensity * 100.0);
+        println!("Climate: {}", climate_line(&climate));
+        println!("Time of day: {}", time_of_day_line(&climate));
+        if let Some(latest) = history.snapshots.last() {
+            println!("Soil Nutrients: {:.1}", latest.soil_nutrients);
+        }
         println!("==============================================\n");
     }
 }
 
+/// Year, season, weather and temperature, e.g. "Year 2, Winter (drought), -3.5°C"
+pub fn climate_line(climate: &Climate) -> String {
+    let weather = match climate.year_kind {
+        YearKind::Normal => "",
+        YearKind::Drought => " (drought)",
+        YearKind::Wet => " (wet year)",
+    };
+    format!(
+        "Year {}, {:?}{weather}, {:.1}°C",
+        climate.year + 1,
+        climate.season,
+        climate.temperature
+    )
+}
+
+/// Clock time and whether it is day or night, e.g. "21:36 (night)"
+pub fn time_of_day_line(climate: &Climate) -> String {
+    let hour = climate.hour();
+    let minute = (hour.fract() * 60.0) as u32;
+    let phase = if climate.is_night() { "night" } else { "day" };
+    format!("{:02}:{minute:02} ({phase})", hour as u32)
+}
+
+/// `name=count` for every species, e.g. for console summaries
+pub fn population_line(stats: &PopulationStats) -> String {
+    stats
+        .species
+        .iter()
+        .map(|(name, count)| format!("{name}={count}"))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
 pub fn ui_system(
     stats: Res<PopulationStats>,
     history: Res<SimulationHistory>,
     sunlight: Res<SunlightLevel>,
+    climate: Res<Climate>,
     mut text: Query<&mut Text>,
 ) {
     for mut text in text.iter_mut() {
-        let mut display = format!(
-            "=== ECOSYSTEM SIMULATION ===\n\n\
-             POPULATION\n\
-             Plants:     {}\n\
-             Prey:       {}\n\
-             Predators:  {}\n\
-             Scavengers: {}\n\n",
-            stats.plants, stats.prey, stats.predators, stats.scavengers
-        );
+        let mut display = String::from("=== ECOSYSTEM SIMULATION ===\n\nPOPULATION\n");
+        for (name, count) in &stats.species {
+            display.push_str(&format!("{:<11} {count}\n", format!("{name}:")));
+        }
+        display.push('\n');
 
         if let Some(latest) = history.snapshots.last() {
             display.push_str(&format!(
-                "ENERGY & AGE\n\
-                 Total Energy: {:.0}\n\
-                 Avg Plant Age: {:.1}s\n\
-                 Avg Prey Age: {:.1}s\n\
-                 Avg Pred Age: {:.1}s\n\n\
-                 TRAITS\n\
-                 Prey Speed: {:.1}\n\
-                 Pred Speed: {:.1}\n\n",
-                latest.total_energy,
-                latest.avg_plant_age,
-                latest.avg_prey_age,
-                latest.avg_predator_age,
-                latest.avg_prey_speed,
-                latest.avg_predator_speed
+                "ENERGY & AGE\nTotal Energy: {:.0}\n",
+                latest.total_energy
             ));
+            for species in &latest.species {
+                display.push_str(&format!(
+                    "Avg {} Age: {:.1}s\n",
+                    species.name, species.avg_age
+                ));
+            }
+
+            display.push_str("\nTRAITS\n");
+            for species in latest.species.iter().filter(|s| s.avg_speed > 0.0) {
+                display.push_str(&format!(
+                    "{} Speed: {:.1}\n",
+                    species.name, species.avg_speed
+                ));
+            }
+
+            display.push_str("\nGENERATIONS (max / mean)\n");
+            for species in &latest.species {
+                display.push_str(&format!(
+                    "{:<11} {} / {:.1}\n",
+                    format!("{}:", species.name),
+                    species.generations.max,
+                    species.generations.mean
+                ));
+            }
+            display.push('\n');
         }
 
+        let soil_nutrients = history.snapshots.last().map_or(0.0, |s| s.soil_nutrients);
         display.push_str(&format!(
             "ENVIRONMENT\n\
-             Sunlight: {:.0}%\n\n\
+             {}\n\
+             Time of Day: {}\n\
+             Sunlight: {:.0}%\n\
+             Soil Nutrients: {:.0}\n\n\
              Time: {:.0}s",
+            climate_line(&climate),
+            time_of_day_line(&climate),
             sunlight.intensity * 100.0,
+            soil_nutrients,
             history.snapshots.len() as f32 * history.record_interval
         ));
 
@@ -118,56 +163,28 @@ pub fn ui_system(
     }
 }
 
-pub fn visual_polish_system(
-    mut plants: VisualPolishPlantQuery,
-    mut prey: VisualPolishPreyQuery,
-    mut predators: VisualPolishPredatorQuery,
-) {
-    // Update plant visuals
-    for (mut sprite, genome, energy) in plants.iter_mut() {
-        let size = 8.0 * genome.size;
+pub fn visual_polish_system(mut organisms: VisualPolishQuery, config: Res<SimulationConfig>) {
+    for (mut sprite, genome, energy, &species, stamina) in organisms.iter_mut() {
+        let species_config = config.species.get(species);
+        let size = species_config.sprite_size * genome.size;
         sprite.custom_size = Some(Vec2::splat(size));
 
-        // Darken color based on energy
-        let energy_factor = (energy.0 / 150.0).clamp(0.3, 1.0);
-        sprite.color = Color::srgb(
-            0.2 * energy_factor,
-            0.8 * energy_factor,
-            0.2 * energy_factor,
-        );
-    }
-
-    // Update prey visuals
-    for (mut sprite, genome, energy, stamina) in prey.iter_mut() {
-        let size = 12.0 * genome.size;
-        sprite.custom_size = Some(Vec2::splat(size));
-
-        // Color based on stamina and energy
-        let stamina_factor = (stamina.current / stamina.max).clamp(0.3, 1.0);
-        let energy_factor = (energy.0 / 100.0).clamp(0.3, 1.0);
-        sprite.color = Color::srgb(
-            0.3 * energy_factor,
-            0.3 * energy_factor,
-            0.9 * stamina_factor,
-        );
-    }
-
-    // Update predator visuals
-    for (mut sprite, genome, energy) in predators.iter_mut() {
-        let size = 16.0 * genome.size;
-        sprite.custom_size = Some(Vec2::splat(size));
-
-        // Color based on energy
-        let energy_factor = (energy.0 / 140.0).clamp(0.3, 1.0);
-        sprite.color = Color::srgb(
-            0.9 * energy_factor,
-            0.2 * energy_factor,
-            0.2 * energy_factor,
-        );
+        // Darken color as energy (and, for foragers, stamina) runs low
+        let energy_factor = (energy.0 / (genome.reproduction_threshold * 1.25)).clamp(0.3, 1.0);
+        let stamina_factor = stamina.map_or(1.0, |stamina| {
+            (stamina.current / stamina.max).clamp(0.3, 1.0)
+        });
+        let factor = (energy_factor * stamina_factor).max(0.3);
+        let (red, green, blue) = species_config.color;
+        sprite.color = Color::srgb(red * factor, green * factor, blue * factor);
     }
 }
 
-pub fn draw_graphs_system(mut gizmos: Gizmos, history: Res<SimulationHistory>) {
+pub fn draw_graphs_system(
+    mut gizmos: Gizmos,
+    history: Res<SimulationHistory>,
+    config: Res<SimulationConfig>,
+) {
     if history.snapshots.len() < 2 {
         return;
     }
@@ -190,38 +207,13 @@ pub fn draw_graphs_system(mut gizmos: Gizmos, history: Res<SimulationHistory>) {
     gizmos.line_2d(top_right, bottom_right, Color::srgba(0.3, 0.3, 0.3, 0.8));
 
     // Find max values for scaling
-    let max_plants = history
-        .snapshots
-        .iter()
-        .map(|s| s.plant_count)
-        .max()
-        .unwrap_or(1)
-        .max(10);
-    let max_prey = history
-        .snapshots
-        .iter()
-        .map(|s| s.prey_count)
-        .max()
-        .unwrap_or(1)
-        .max(10);
-    let max_predators = history
-        .snapshots
-        .iter()
-        .map(|s| s.predator_count)
-        .max()
-        .unwrap_or(1)
-        .max(10);
-    let max_scavengers = history
+    let max_pop = history
         .snapshots
         .iter()
-        .map(|s| s.scavenger_count)
+        .flat_map(|s| s.species.iter().map(|species| species.count))
         .max()
         .unwrap_or(1)
-        .max(10);
-    let max_pop = max_plants
-        .max(max_prey)
-        .max(max_predators)
-        .max(max_scavengers) as f32;
+        .max(10) as f32;
 
     // Draw data points (last 100 snapshots)
     let start_idx = history.snapshots.len().saturating_sub(100);
@@ -231,49 +223,24 @@ pub fn draw_graphs_system(mut gizmos: Gizmos, history: Res<SimulationHistory>) {
         let x1 = graph_x + (i as f32 / visible_snapshots.len() as f32) * graph_width;
         let x2 = graph_x + ((i + 1) as f32 / visible_snapshots.len() as f32) * graph_width;
 
-        // Plants (green)
-        let y1_plants = graph_y - graph_height
-            + (visible_snapshots[i].plant_count as f32 / max_pop) * graph_height;
-        let y2_plants = graph_y - graph_height
-            + (visible_snapshots[i + 1].plant_count as f32 / max_pop) * graph_height;
-        gizmos.line_2d(
-            Vec2::new(x1, y1_plants),
-            Vec2::new(x2, y2_plants),
-            Color::srgb(0.2, 0.9, 0.2),
-        );
-
-        // Prey (blue)
-        let y1_prey = graph_y - graph_height
-            + (visible_snapshots[i].prey_count as f32 / max_pop) * graph_height;
-        let y2_prey = graph_y - graph_height
-            + (visible_snapshots[i + 1].prey_count as f32 / max_pop) * graph_height;
-        gizmos.line_2d(
-            Vec2::new(x1, y1_prey),
-            Vec2::new(x2, y2_prey),
-            Color::srgb(0.3, 0.3, 0.9),
-        );
-
-        // Predators (red)
-        let y1_pred = graph_y - graph_height
-            + (visible_snapshots[i].predator_count as f32 / max_pop) * graph_height;
-        let y2_pred = graph_y - graph_height
-            + (visible_snapshots[i + 1].predator_count as f32 / max_pop) * graph_height;
-        gizmos.line_2d(
-            Vec2::new(x1, y1_pred),
-            Vec2::new(x2, y2_pred),
-            Color::srgb(0.9, 0.2, 0.2),
-        );
-
-        // Scavengers (brown)
-        let y1_scav = graph_y - graph_height
-            + (visible_snapshots[i].scavenger_count as f32 / max_pop) * graph_height;
-        let y2_scav = graph_y - graph_height
-            + (visible_snapshots[i + 1].scavenger_count as f32 / max_pop) * graph_height;
-        gizmos.line_2d(
-            Vec2::new(x1, y1_scav),
-            Vec2::new(x2, y2_scav),
-            Color::srgb(0.7, 0.5, 0.2),
-        );
+        // One line per species, in its own color
+        for (species, species_config) in config.species.iter() {
+            let count = |snapshot: &SimulationSnapshot| {
+                snapshot
+                    .species
+                    .get(species.0)
+                    .map_or(0, |species| species.count)
+            };
+            let y1 = graph_y - graph_height
+                + (count(&visible_snapshots[i]) as f32 / max_pop) * graph_height;
+            let y2 = graph_y - graph_height
+                + (count(&visible_snapshots[i + 1]) as f32 / max_pop) * graph_height;
+            gizmos.line_2d(
+                Vec2::new(x1, y1),
+                Vec2::new(x2, y2),
+                srgb(species_config.color),
+            );
+        }
     }
 
     // Energy graph below
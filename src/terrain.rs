@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::resources::TerrainConfig;
+use crate::utils::{CellLayout, splitmix64};
+
+// ===== TERRAIN =====
+
+/// The kind of land in one terrain cell
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum Biome {
+    #[default]
+    Grassland,
+    Forest,
+    Desert,
+    Marsh,
+}
+
+/// Biomes on a coarse grid over the torus world, generated once from
+/// seeded noise when the world is built
+#[derive(Resource, Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BiomeMap {
+    layout: CellLayout,
+    cells: Vec<Biome>,
+}
+
+impl BiomeMap {
+    /// Dry patches become desert and wet ones marsh; in between, a second
+    /// noise field decides between forest and grassland
+    pub fn generate(world_size: Vec2, config: &TerrainConfig, seed: u64) -> Self {
+        let layout = CellLayout::new(world_size, config.cell_size);
+        let moisture = TorusNoise::new(seed, world_size, config.patch_size);
+        let woodland = TorusNoise::new(seed ^ 0x5EED_F0E5, world_size, config.patch_size);
+
+        let cells = (0..layout.len())
+            .map(|index| {
+                let position = layout.center(index);
+                let wetness = moisture.sample(position);
+                if wetness < 0.35 {
+                    Biome::Desert
+                } else if wetness > 0.65 {
+                    Biome::Marsh
+                } else if woodland.sample(position) > 0.55 {
+                    Biome::Forest
+                } else {
+                    Biome::Grassland
+                }
+            })
+            .collect();
+        Self { layout, cells }
+    }
+
+    /// Every cell the same biome
+    pub fn uniform(world_size: Vec2, config: &TerrainConfig, biome: Biome) -> Self {
+        let layout = CellLayout::new(world_size, config.cell_size);
+        Self {
+            layout,
+            cells: vec![biome; layout.len()],
+        }
+    }
+
+    /// The biome at `position`; grassland on an empty map
+    pub fn at(&self, position: Vec2) -> Biome {
+        if self.layout.is_empty() {
+            return Biome::default();
+        }
+        self.cells[self.layout.index_of(position)]
+    }
+
+    /// Set the biome of the cell containing `position`
+    pub fn set(&mut self, position: Vec2, biome: Biome) {
+        if !self.layout.is_empty() {
+            let index = self.layout.index_of(position);
+            self.cells[index] = biome;
+        }
+    }
+
+    /// The grid size in cells, as (columns, rows)
+    pub fn dimensions(&self) -> (usize, usize) {
+        (self.layout.columns, self.layout.rows)
+    }
+
+    /// Each cell's biome, row by row from the bottom-left corner
+    pub fn cells(&self) -> &[Biome] {
+        &self.cells
+    }
+}
+
+/// Smooth value noise in `0..1` that tiles seamlessly across the world
+/// edges: two octaves over a lattice with a whole number of points per axis
+struct TorusNoise {
+    seed: u64,
+    world_size: Vec2,
+    lattice: (i64, i64),
+}
+
+impl TorusNoise {
+    fn new(seed: u64, world_size: Vec2, patch_size: f32) -> Self {
+        let points = |extent: f32| ((extent / patch_size).round() as i64).max(1);
+        Self {
+            seed,
+            world_size,
+            lattice: (points(world_size.x), points(world_size.y)),
+        }
+    }
+
+    fn sample(&self, position: Vec2) -> f32 {
+        let unit = (position + self.world_size / 2.0) / self.world_size;
+        let coarse = self.octave(unit, 1, self.seed);
+        let fine = self.octave(unit, 2, self.seed.wrapping_add(1));
+        (coarse + fine * 0.5) / 1.5
+    }
+
+    /// Bilinear value noise with smoothstep easing, `scale` lattice cells
+    /// per base lattice cell
+    fn octave(&self, unit: Vec2, scale: i64, seed: u64) -> f32 {
+        let (columns, rows) = (self.lattice.0 * scale, self.lattice.1 * scale);
+        let x = unit.x * columns as f32;
+        let y = unit.y * rows as f32;
+        let (x0, y0) = (x.floor() as i64, y.floor() as i64);
+        let ease = |t: f32| t * t * (3.0 - 2.0 * t);
+        let (tx, ty) = (ease(x - x0 as f32), ease(y - y0 as f32));
+
+        let value = |dx: i64, dy: i64| {
+            lattice_value(
+                seed,
+                (x0 + dx).rem_euclid(columns),
+                (y0 + dy).rem_euclid(rows),
+            )
+        };
+        let bottom = value(0, 0) + (value(1, 0) - value(0, 0)) * tx;
+        let top = value(0, 1) + (value(1, 1) - value(0, 1)) * tx;
+        bottom + (top - bottom) * ty
+    }
+}
+
+/// A fixed pseudo-random value in `0..1` for one lattice point
+fn lattice_value(seed: u64, x: i64, y: i64) -> f32 {
+    let z = splitmix64(
+        seed ^ (x as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (y as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F),
+    );
+    (z >> 40) as f32 / (1u64 << 24) as f32
+}
@@ -1,135 +1,55 @@
 #[cfg(test)]
 mod tests {
-    use bevy::app::ScheduleRunnerPlugin;
     use bevy::prelude::*;
-    use rand::Rng;
+    use bevy::time::TimeUpdateStrategy;
     use std::time::Duration;
 
+    use crate::ConfigHotReloadPlugin;
+    use crate::cli::CliArgs;
     use crate::components::*;
+    use crate::config::ConfigError;
+    use crate::headless::create_headless_app;
+    use crate::obstacles::{Obstacle, ObstacleKind, Obstacles};
+    use crate::pathfinding::FlowFields;
+    use crate::phylogeny::BirthDeathLedger;
     use crate::resources::*;
-    use crate::systems::*;
+    use crate::save::*;
+    use crate::soil::SoilNutrients;
+    use crate::spatial::{GridLayer, SpatialGrid};
+    use crate::species::*;
+    use crate::systems::population_line;
+    use crate::terrain::{Biome, BiomeMap};
+    use crate::utils::wrap_position;
 
-    fn create_test_app() -> App {
-        let mut app = App::new();
-        app.add_plugins(MinimalPlugins.set(ScheduleRunnerPlugin::run_loop(
-            Duration::from_secs_f64(1.0 / 60.0),
-        )))
-        .init_resource::<SimulationConfig>()
-        .init_resource::<PopulationStats>()
-        .init_resource::<SunlightLevel>()
-        .insert_resource(SimulationHistory {
-            snapshots: Vec::new(),
-            record_interval: 1.0,
-            time_since_last_record: 0.0,
-        })
-        .add_systems(Startup, setup_test)
-        .add_systems(
-            Update,
-            (
-                sunlight_cycle_system,
-                plant_growth_system,
-                plant_respawn_system,
-                immigration_system,
-                prey_movement_system,
-                predator_hunting_system,
-                scavenger_movement_system,
-                eating_system,
-                energy_consumption_system,
-                age_system,
-                reproduction_system,
-                death_system,
-                corpse_decay_system,
-                update_population_stats,
-                record_history_system,
-            )
-                .chain(),
-        );
-        app
+    /// Living organisms of the named species, in query order
+    fn species_entities(app: &mut App, name: &str) -> Vec<Entity> {
+        let species = app
+            .world()
+            .resource::<SimulationConfig>()
+            .species
+            .id_of(name)
+            .expect("species should exist");
+        let mut query = app.world_mut().query::<(Entity, &SpeciesId)>();
+        query
+            .iter(app.world())
+            .filter(|&(_, &id)| id == species)
+            .map(|(entity, _)| entity)
+            .collect()
     }
 
-    fn setup_test(mut commands: Commands, config: Res<SimulationConfig>) {
-        let mut rng = rand::rng();
-
-        // Spawn plants
-        for _ in 0..config.initial_plants {
-            let x = rng.random_range(-config.world_size.x / 2.0..config.world_size.x / 2.0);
-            let y = rng.random_range(-config.world_size.y / 2.0..config.world_size.y / 2.0);
-            commands.spawn((
-                Plant,
-                Genome::random_plant(),
-                Energy(rng.random_range(20.0..50.0)),
-                Age(0.0),
-                Transform::from_xyz(x, y, 0.0),
-            ));
-        }
-
-        // Spawn prey
-        for _ in 0..config.initial_prey {
-            let x = rng.random_range(-config.world_size.x / 2.0..config.world_size.x / 2.0);
-            let y = rng.random_range(-config.world_size.y / 2.0..config.world_size.y / 2.0);
-            commands.spawn((
-                Prey,
-                Genome::random_prey(),
-                Energy(rng.random_range(40.0..80.0)),
-                Age(0.0),
-                Velocity(Vec2::ZERO),
-                Stamina::default(),
-                Transform::from_xyz(x, y, 1.0),
-            ));
-        }
-
-        // Spawn predators
-        for _ in 0..config.initial_predators {
-            let x = rng.random_range(-config.world_size.x / 2.0..config.world_size.x / 2.0);
-            let y = rng.random_range(-config.world_size.y / 2.0..config.world_size.y / 2.0);
-
-            let waypoint_angle = rng.random_range(0.0..std::f32::consts::TAU);
-            let waypoint_distance = rng.random_range(100.0..200.0);
-            let waypoint_target = Vec2::new(
-                x + waypoint_angle.cos() * waypoint_distance,
-                y + waypoint_angle.sin() * waypoint_distance,
-            );
-
-            commands.spawn((
-                Predator,
-                Genome::random_predator(),
-                Energy(rng.random_range(60.0..100.0)),
-                Age(0.0),
-                Velocity(Vec2::ZERO),
-                HuntTarget(None),
-                ExplorationWaypoint {
-                    target: waypoint_target,
-                    reached_threshold: 30.0,
-                },
-                Transform::from_xyz(x, y, 2.0),
-            ));
-        }
-
-        // Spawn scavengers
-        for _ in 0..config.initial_scavengers {
-            let x = rng.random_range(-config.world_size.x / 2.0..config.world_size.x / 2.0);
-            let y = rng.random_range(-config.world_size.y / 2.0..config.world_size.y / 2.0);
-
-            let waypoint_angle = rng.random_range(0.0..std::f32::consts::TAU);
-            let waypoint_distance = rng.random_range(100.0..200.0);
-            let waypoint_target = Vec2::new(
-                x + waypoint_angle.cos() * waypoint_distance,
-                y + waypoint_angle.sin() * waypoint_distance,
-            );
+    /// The named species' definition, for tweaking test configs
+    fn species_mut<'a>(config: &'a mut SimulationConfig, name: &str) -> &'a mut SpeciesConfig {
+        config
+            .species
+            .0
+            .iter_mut()
+            .find(|species| species.name == name)
+            .expect("species should exist")
+    }
 
-            commands.spawn((
-                Scavenger,
-                Genome::random_scavenger(),
-                Energy(rng.random_range(50.0..90.0)),
-                Age(0.0),
-                Velocity(Vec2::ZERO),
-                ExplorationWaypoint {
-                    target: waypoint_target,
-                    reached_threshold: 30.0,
-                },
-                Transform::from_xyz(x, y, 1.5),
-            ));
-        }
+    /// The default world, advancing one fixed tick per `app.update()`
+    fn create_test_app() -> App {
+        create_headless_app(SimulationConfig::default())
     }
 
     #[test]
@@ -142,16 +62,13 @@ mod tests {
         }
 
         let stats = app.world().resource::<PopulationStats>();
-        println!(
-            "After 60s - Plants: {}, Prey: {}, Predators: {}, Scavengers: {}",
-            stats.plants, stats.prey, stats.predators, stats.scavengers
-        );
+        println!("After 60s - {}", population_line(stats));
 
         // At least one of each species should survive
-        assert!(stats.plants > 0, "Plants went extinct");
-        assert!(stats.prey > 0, "Prey went extinct");
-        assert!(stats.predators > 0, "Predators went extinct");
-        assert!(stats.scavengers > 0, "Scavengers went extinct");
+        assert!(stats.count("plant") > 0, "Plants went extinct");
+        assert!(stats.count("prey") > 0, "Prey went extinct");
+        assert!(stats.count("predator") > 0, "Predators went extinct");
+        assert!(stats.count("scavenger") > 0, "Scavengers went extinct");
     }
 
     #[test]
@@ -164,28 +81,17 @@ mod tests {
         }
 
         let stats = app.world().resource::<PopulationStats>();
-        println!(
-            "After 30s - Plants: {}, Prey: {}, Predators: {}, Scavengers: {}",
-            stats.plants, stats.prey, stats.predators, stats.scavengers
-        );
+        println!("After 30s - {}", population_line(stats));
 
         // All species should still be alive after 30 seconds (scaled 4× for larger world)
-        assert!(
-            stats.plants > 40,
-            "Too few plants survived ({})",
-            stats.plants
-        );
-        assert!(stats.prey > 20, "Too few prey survived ({})", stats.prey);
-        assert!(
-            stats.predators > 8,
-            "Too few predators survived ({})",
-            stats.predators
-        );
-        assert!(
-            stats.scavengers > 8,
-            "Too few scavengers survived ({})",
-            stats.scavengers
-        );
+        let plants = stats.count("plant");
+        let prey = stats.count("prey");
+        let predators = stats.count("predator");
+        let scavengers = stats.count("scavenger");
+        assert!(plants > 40, "Too few plants survived ({plants})");
+        assert!(prey > 20, "Too few prey survived ({prey})");
+        assert!(predators > 8, "Too few predators survived ({predators})");
+        assert!(scavengers > 8, "Too few scavengers survived ({scavengers})");
     }
 
     #[test]
@@ -198,31 +104,22 @@ mod tests {
         }
 
         let stats = app.world().resource::<PopulationStats>();
-        println!(
-            "After 90s - Plants: {}, Prey: {}, Predators: {}, Scavengers: {}",
-            stats.plants, stats.prey, stats.predators, stats.scavengers
-        );
+        println!("After 90s - {}", population_line(stats));
 
         // Populations shouldn't explode (limits scaled 4× for larger world)
+        let plants = stats.count("plant");
+        let prey = stats.count("prey");
+        let predators = stats.count("predator");
+        let scavengers = stats.count("scavenger");
+        assert!(plants < 2000, "Plant population exploded ({plants})");
+        assert!(prey < 800, "Prey population exploded ({prey})");
         assert!(
-            stats.plants < 2000,
-            "Plant population exploded ({})",
-            stats.plants
-        );
-        assert!(
-            stats.prey < 800,
-            "Prey population exploded ({})",
-            stats.prey
+            predators < 200,
+            "Predator population exploded ({predators})"
         );
         assert!(
-            stats.predators < 200,
-            "Predator population exploded ({})",
-            stats.predators
-        );
-        assert!(
-            stats.scavengers < 200,
-            "Scavenger population exploded ({})",
-            stats.scavengers
+            scavengers < 200,
+            "Scavenger population exploded ({scavengers})"
         );
     }
 
@@ -262,90 +159,1640 @@ mod tests {
     fn test_immigration_prevents_extinction() {
         let mut app = create_test_app();
 
-        // Manually kill off most animals to trigger immigration
-        let prey_entities: Vec<Entity> = app
+        // Let Startup spawn the founders before culling them
+        app.update();
+
+        // Manually kill off most animals to trigger immigration, all but 2
+        // of each animal species
+        for name in ["prey", "omnivore", "predator", "apex", "scavenger"] {
+            for entity in species_entities(&mut app, name).into_iter().skip(2) {
+                app.world_mut().despawn(entity);
+            }
+        }
+
+        app.update();
+        let stats_before = app.world().resource::<PopulationStats>().clone();
+        println!("Before immigration - {}", population_line(&stats_before));
+
+        // Run for 120 seconds to allow immigration to trigger multiple times
+        // At 20% chance per second, we should see immigration events
+        for _ in 0..7200 {
+            app.update();
+        }
+
+        let stats_after = app.world().resource::<PopulationStats>().clone();
+        println!("After 120s - {}", population_line(&stats_after));
+
+        // Immigration should have brought populations back up
+        for name in ["prey", "predator", "scavenger"] {
+            assert!(
+                stats_after.count(name) >= 2,
+                "{name} population didn't recover via immigration ({})",
+                stats_after.count(name)
+            );
+        }
+
+        // At least one species should have increased from immigration
+        let animals = |stats: &PopulationStats| {
+            stats.count("prey") + stats.count("predator") + stats.count("scavenger")
+        };
+        let total_before = animals(&stats_before);
+        let total_after = animals(&stats_after);
+        assert!(
+            total_after >= total_before,
+            "No population recovery occurred (before: {}, after: {})",
+            total_before,
+            total_after
+        );
+    }
+
+    #[test]
+    fn test_headless_app_advances_fixed_ticks() {
+        let mut app = create_headless_app(SimulationConfig::default());
+
+        // 10 simulated seconds at 60 ticks per second
+        for _ in 0..600 {
+            app.update();
+        }
+
+        let elapsed = app.world().resource::<Time>().elapsed_secs();
+        assert!(
+            (elapsed - 10.0).abs() < 0.1,
+            "Headless time drifted ({elapsed:.3}s)"
+        );
+
+        let history = app.world().resource::<SimulationHistory>();
+        assert!(
+            history.snapshots.len() >= 9,
+            "History not recorded in headless mode ({})",
+            history.snapshots.len()
+        );
+
+        // No rendering data should exist without the windowed app
+        let mut sprites = app.world_mut().query::<&Sprite>();
+        assert_eq!(sprites.iter(app.world()).count(), 0);
+    }
+
+    #[test]
+    fn test_cli_parsing() {
+        let args = |list: &[&str]| CliArgs::parse(list.iter().map(|s| s.to_string()));
+
+        assert_eq!(args(&[]).unwrap(), CliArgs::default());
+
+        let headless = args(&["--headless", "--duration", "120"]).unwrap();
+        assert!(headless.headless);
+        assert_eq!(headless.duration, Some(120.0));
+
+        let ticks = args(&["--headless", "--ticks", "5000"]).unwrap();
+        assert_eq!(ticks.ticks, Some(5000));
+
+        let seeded = args(&["--seed", "7"]).unwrap();
+        assert_eq!(seeded.config().unwrap().seed, Some(7));
+
+        assert!(args(&["--duration", "10"]).is_err());
+        assert!(args(&["--headless", "--duration", "10", "--ticks", "5"]).is_err());
+        assert!(args(&["--headless", "--ticks"]).is_err());
+        assert!(args(&["--headless", "--ticks", "abc"]).is_err());
+        assert!(args(&["--bogus"]).is_err());
+    }
+
+    /// Energy of an organism, or biomass of a corpse
+    fn food_value(energy: Option<&Energy>, corpse: Option<&Corpse>) -> f32 {
+        energy.map_or_else(|| corpse.map_or(0.0, |corpse| corpse.biomass), |e| e.0)
+    }
+
+    /// Positions and energies of every organism, as raw bits in query order
+    fn world_fingerprint(app: &mut App) -> Vec<(u32, u32, u32)> {
+        let mut query = app
             .world_mut()
-            .query_filtered::<Entity, With<Prey>>()
+            .query_filtered::<(&Transform, Option<&Energy>, Option<&Corpse>), With<Genome>>();
+        query
             .iter(app.world())
-            .collect();
+            .map(|(transform, energy, corpse)| {
+                (
+                    transform.translation.x.to_bits(),
+                    transform.translation.y.to_bits(),
+                    food_value(energy, corpse).to_bits(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_same_seed_is_deterministic() {
+        let run = |seed: u64| {
+            let mut app = create_headless_app(SimulationConfig {
+                seed: Some(seed),
+                ..default()
+            });
+            for _ in 0..600 {
+                app.update();
+            }
+            let stats = app.world().resource::<PopulationStats>().clone();
+            (stats.species, world_fingerprint(&mut app))
+        };
 
-        // Kill all but 2 prey
-        for (i, entity) in prey_entities.iter().enumerate() {
-            if i >= 2 {
-                app.world_mut().despawn(*entity);
+        let first = run(42);
+        let second = run(42);
+        assert_eq!(first.0, second.0, "Populations diverged with the same seed");
+        assert!(
+            first.1 == second.1,
+            "Organism state diverged with the same seed"
+        );
+
+        let other = run(43);
+        assert!(
+            first.1 != other.1,
+            "Different seeds produced the same world"
+        );
+    }
+
+    #[test]
+    fn test_tick_rate_independent_of_frame_rate() {
+        // Render 10 simulated seconds at 30 Hz and at 144 Hz; the fixed
+        // simulation should run the same ticks and reach the same world
+        // either way
+        let run = |frame_rate: f64| {
+            let mut app = create_headless_app(SimulationConfig {
+                seed: Some(7),
+                ..default()
+            });
+            let frame = Duration::from_secs_f64(1.0 / frame_rate);
+            let timestep = app.world().resource::<Time<Fixed>>().timestep();
+            let mut frames = 0;
+            loop {
+                // The last frame is cut short to end exactly on tick 600
+                let fixed = app.world().resource::<Time<Fixed>>();
+                let remaining = (timestep * 600).saturating_sub(fixed.elapsed() + fixed.overstep());
+                if remaining.is_zero() {
+                    break;
+                }
+                app.insert_resource(TimeUpdateStrategy::ManualDuration(frame.min(remaining)));
+                app.update();
+                frames += 1;
+            }
+            let ticks =
+                app.world().resource::<Time<Fixed>>().elapsed().as_nanos() / timestep.as_nanos();
+            let stats = app.world().resource::<PopulationStats>().clone();
+            (frames, ticks, stats.species, world_fingerprint(&mut app))
+        };
+
+        let slow = run(30.0);
+        let fast = run(144.0);
+        assert!(
+            (300..=302).contains(&slow.0),
+            "30 Hz run took {} frames",
+            slow.0
+        );
+        assert!(
+            (1440..=1442).contains(&fast.0),
+            "144 Hz run took {} frames",
+            fast.0
+        );
+        assert_eq!(
+            (slow.1, fast.1),
+            (600, 600),
+            "Runs simulated different ticks"
+        );
+        assert_eq!(slow.2, fast.2, "Populations depend on the frame rate");
+        assert!(slow.3 == fast.3, "Organism state depends on the frame rate");
+    }
+
+    #[test]
+    fn test_bundled_config_matches_defaults() {
+        let config = SimulationConfig::load("config/default.ron").unwrap();
+        assert_eq!(config, SimulationConfig::default());
+    }
+
+    #[test]
+    fn test_config_validation() {
+        // Partial files keep defaults for everything not mentioned
+        let partial = SimulationConfig::from_ron("(max_age: 120.0, seed: Some(3))").unwrap();
+        assert_eq!(partial.max_age, 120.0);
+        assert_eq!(partial.seed, Some(3));
+        assert_eq!(partial.species, SimulationConfig::default().species);
+        assert!(partial.validate().is_ok());
+
+        // Typos are rejected rather than silently ignored
+        assert!(SimulationConfig::from_ron("(max_agee: 10.0)").is_err());
+
+        let mut broken = SimulationConfig {
+            corpse_decay_time: 0.0,
+            ..default()
+        };
+        let plant = species_mut(&mut broken, "plant");
+        plant.initial_count = 500;
+        plant.respawn = Some(Respawn {
+            rate: 2.0,
+            max_population: 100,
+        });
+        species_mut(&mut broken, "prey").reproduction_rate = -0.5;
+        let Err(ConfigError::Invalid(problems)) = broken.validate() else {
+            panic!("Invalid config passed validation");
+        };
+        assert_eq!(problems.len(), 3, "Unexpected problems: {problems:?}");
+        assert!(problems.iter().any(|p| p.contains("max_population")));
+        assert!(
+            problems
+                .iter()
+                .any(|p| p.contains("'prey' reproduction_rate"))
+        );
+        assert!(problems.iter().any(|p| p.contains("corpse_decay_time")));
+
+        // A population cap below the founders stops every way a config loads:
+        // from a file and from a save
+        let mut capped = SimulationConfig {
+            seed: Some(3),
+            ..default()
+        };
+        species_mut(&mut capped, "plant").respawn = Some(Respawn {
+            rate: 2.0,
+            max_population: 100,
+        });
+        let is_capped = |error: &ConfigError| {
+            matches!(error, ConfigError::Invalid(problems)
+                if problems.iter().any(|p| p.contains("max_population")))
+        };
+        let path = std::env::temp_dir().join(format!("capped-{}.ron", std::process::id()));
+        std::fs::write(&path, ron::to_string(&capped).unwrap()).unwrap();
+        assert!(SimulationConfig::load(&path).is_err_and(|error| is_capped(&error)));
+
+        let mut app = create_headless_app(SimulationConfig {
+            seed: Some(3),
+            ..default()
+        });
+        app.update();
+        let mut save = SaveFile::capture(app.world_mut());
+        save.config = capped;
+        save.write(&path).unwrap();
+        let loaded = SaveFile::read(&path);
+        std::fs::remove_file(&path).ok();
+        assert!(matches!(loaded, Err(SaveError::InvalidConfig(ref error)) if is_capped(error)));
+
+        // Pathfinding grids need a real cell size and room for one field
+        let mut broken = SimulationConfig::default();
+        broken.pathfinding.cell_size = 0.0;
+        broken.pathfinding.max_cached_fields = 0;
+        let Err(ConfigError::Invalid(problems)) = broken.validate() else {
+            panic!("Invalid pathfinding config passed validation");
+        };
+        assert_eq!(problems.len(), 2, "Unexpected problems: {problems:?}");
+        assert!(problems.iter().any(|p| p.contains("pathfinding.cell_size")));
+        assert!(
+            problems
+                .iter()
+                .any(|p| p.contains("pathfinding.max_cached_fields"))
+        );
+
+        // Crowding distances must be positive, and a target takes a hunter
+        let mut broken = SimulationConfig::default();
+        broken.crowding.hunter_spacing = 0.0;
+        broken.crowding.carcass_appetite = f32::NAN;
+        broken.crowding.max_hunters_per_target = 0;
+        species_mut(&mut broken, "plant").max_energy = -1.0;
+        let Err(ConfigError::Invalid(problems)) = broken.validate() else {
+            panic!("Invalid crowding config passed validation");
+        };
+        assert_eq!(problems.len(), 4, "Unexpected problems: {problems:?}");
+        for field in [
+            "hunter_spacing",
+            "carcass_appetite",
+            "max_hunters_per_target",
+            "max_energy",
+        ] {
+            assert!(problems.iter().any(|p| p.contains(field)));
+        }
+
+        // Years and days must take time, or the calendar turns into NaN
+        let mut broken = SimulationConfig::default();
+        broken.climate.year_length = 0.0;
+        broken.climate.day_length = -20.0;
+        let Err(ConfigError::Invalid(problems)) = broken.validate() else {
+            panic!("Invalid climate config passed validation");
+        };
+        assert_eq!(problems.len(), 2, "Unexpected problems: {problems:?}");
+        assert!(problems.iter().any(|p| p.contains("climate.year_length")));
+        assert!(problems.iter().any(|p| p.contains("climate.day_length")));
+    }
+
+    #[test]
+    fn test_species_defined_in_data() {
+        // A new species, added purely in the config file
+        let text = std::fs::read_to_string("config/default.ron").unwrap();
+        let beetle = r#"(
+            name: "beetle",
+            behavior: Forager,
+            initial_count: 25,
+            initial_energy: (30.0, 60.0),
+            genome: (
+                speed: (40.0, 60.0),
+                size: (0.5, 0.8),
+                metabolism: (0.4, 0.6),
+                reproduction_threshold: (50.0, 70.0),
+                vision_range: (60.0, 90.0),
+            ),
+            reproduction_rate: 0.2,
+            color: (0.1, 0.1, 0.1),
+        ),
+    ],
+"#;
+        let grazing = r#"food_web: [
+        (consumer: "beetle", resource: "plant", mode: Graze, radius: 10.0, efficiency: 1.0, bite: 1200.0, min_energy: 20.0),"#;
+        let (species, rest) = text.split_once("\n    ],\n").unwrap();
+        let text = format!("{species}\n{beetle}{rest}").replacen("food_web: [", grazing, 1);
+        let mut config = SimulationConfig::from_ron(&text).unwrap();
+        config.validate().unwrap();
+        config.seed = Some(4);
+        assert_eq!(config.species.len(), 7);
+
+        let mut app = create_headless_app(config);
+        app.update();
+        let beetles = species_entities(&mut app, "beetle");
+        assert_eq!(beetles.len(), 25);
+        for &beetle in &beetles {
+            let entity = app.world().entity(beetle);
+            let genome = entity.get::<Genome>().unwrap();
+            assert!((40.0..60.0).contains(&genome.speed));
+            assert!(entity.contains::<Forager>() && entity.contains::<Stamina>());
+            assert!(!entity.contains::<HuntTarget>());
+        }
+
+        // Predators now see beetles as neither food nor threat, but the new
+        // species still takes part in the whole lifecycle
+        for _ in 0..600 {
+            app.update();
+        }
+        let stats = app.world().resource::<PopulationStats>();
+        assert_eq!(stats.species.len(), 7);
+        assert!(stats.count("beetle") > 0);
+        let history = app.world().resource::<SimulationHistory>();
+        let latest = history.snapshots.last().unwrap();
+        assert!(latest.species("beetle").is_some_and(|s| s.avg_speed > 0.0));
+
+        // Food-web links must name existing species
+        let mut unknown = SimulationConfig::default();
+        unknown.food_web.0[1].resource = "dragon".into();
+        let Err(ConfigError::Invalid(problems)) = unknown.validate() else {
+            panic!("Unknown food-web species passed validation");
+        };
+        assert!(problems[0].contains("'dragon'"), "{problems:?}");
+    }
+
+    #[test]
+    fn test_omnivores_only_catch_smaller_prey() {
+        // A hungry omnivore with no plants around and prey of a fixed size
+        let run = |prey_size: f32| {
+            let mut config = SimulationConfig {
+                world_size: Vec2::new(200.0, 200.0),
+                seed: Some(5),
+                ..default()
+            };
+            for species in &mut config.species.0 {
+                species.initial_count = 0;
+                species.immigration = None;
+                species.respawn = None;
+                species.reproduction_rate = 0.0;
+            }
+            let omnivore = species_mut(&mut config, "omnivore");
+            omnivore.initial_count = 1;
+            omnivore.initial_energy = (60.0, 60.0);
+            omnivore.genome.size = (2.0, 2.0);
+            omnivore.genome.speed = (150.0, 150.0);
+            omnivore.genome.reproduction_threshold = (150.0, 150.0);
+            let prey = species_mut(&mut config, "prey");
+            prey.initial_count = 6;
+            // Enough energy that no prey starves during the run
+            prey.initial_energy = (500.0, 500.0);
+            prey.genome.size = (prey_size, prey_size);
+            prey.genome.speed = (50.0, 50.0);
+
+            let mut app = create_headless_app(config);
+            for _ in 0..600 {
+                app.update();
             }
+            let stats = app.world().resource::<PopulationStats>();
+            assert_eq!(stats.count("omnivore"), 1);
+            let history = app.world().resource::<SimulationHistory>();
+            let latest = history.snapshots.last().unwrap();
+            assert!(latest.species("omnivore").is_some_and(|s| s.count == 1));
+            stats.count("prey")
+        };
+
+        assert_eq!(run(3.0), 6);
+        assert!(run(1.0) < 6);
+    }
+
+    #[test]
+    fn test_apex_predators_hunt_predators() {
+        let mut config = SimulationConfig {
+            world_size: Vec2::new(400.0, 400.0),
+            seed: Some(11),
+            ..default()
+        };
+        for species in &mut config.species.0 {
+            species.initial_count = 0;
+            species.immigration = None;
+            species.reproduction_rate = 0.0;
         }
+        species_mut(&mut config, "plant").respawn = None;
+        species_mut(&mut config, "apex").initial_count = 2;
+        let predator = species_mut(&mut config, "predator");
+        predator.initial_count = 8;
+        predator.initial_energy = (300.0, 300.0);
+        let mut app = create_headless_app(config);
+        app.update();
 
-        let predator_entities: Vec<Entity> = app
+        // Apex predators claim predators with the usual hunting logic
+        let apexes = species_entities(&mut app, "apex");
+        let mut claimed = false;
+        for _ in 0..600 {
+            app.update();
+            let predators = species_entities(&mut app, "predator");
+            claimed |= apexes.iter().any(|&apex| {
+                app.world()
+                    .get::<HuntTarget>(apex)
+                    .and_then(|target| target.0)
+                    .is_some_and(|target| predators.contains(&target))
+            });
+        }
+        assert!(claimed);
+        let stats = app.world().resource::<PopulationStats>();
+        assert!(stats.count("predator") < 8);
+        assert_eq!(stats.count("apex"), 2);
+    }
+
+    #[test]
+    fn test_kills_leave_carcasses() {
+        let mut config = SimulationConfig {
+            world_size: Vec2::new(300.0, 300.0),
+            seed: Some(6),
+            ..default()
+        };
+        for species in &mut config.species.0 {
+            species.initial_count = 0;
+            species.immigration = None;
+            species.respawn = None;
+            species.reproduction_rate = 0.0;
+        }
+        let predator = species_mut(&mut config, "predator");
+        predator.initial_count = 1;
+        predator.initial_energy = (50.0, 50.0);
+        predator.genome.speed = (150.0, 150.0);
+        // Sees the prey from anywhere in the world, even in the dark
+        predator.genome.vision_range = (400.0, 400.0);
+        let prey = species_mut(&mut config, "prey");
+        prey.initial_count = 1;
+        prey.initial_energy = (300.0, 300.0);
+        prey.genome.speed = (30.0, 30.0);
+        prey.genome.size = (2.0, 2.0);
+        let bite = config
+            .food_web
+            .0
+            .iter()
+            .find(|link| {
+                link.consumer == "predator"
+                    && link.resource == "prey"
+                    && link.mode == FeedingMode::Kill
+            })
+            .expect("predators should kill prey")
+            .bite
+            / config.tick_rate as f32;
+        let biomass = 2.0 * species_mut(&mut config, "prey").carcass_biomass;
+
+        let mut app = create_headless_app(config);
+        app.update();
+        let prey = species_entities(&mut app, "prey")[0];
+
+        // The catch stays behind as a carcass holding all but the first
+        // tick's bite (and what rotted in the meantime)...
+        let mut carcass = None;
+        for _ in 0..1200 {
+            app.update();
+            if let Some(corpse) = app.world().get::<Corpse>(prey) {
+                carcass = Some(corpse.biomass);
+                break;
+            }
+        }
+        let carcass = carcass.expect("the prey should have been caught");
+        assert!(carcass <= biomass - bite && carcass > biomass - bite - 1.0);
+
+        // ...which is then eaten over several more bites
+        app.update();
+        let left = app.world().get::<Corpse>(prey).map_or(0.0, |c| c.biomass);
+        assert!(left < carcass);
+
+        // An omnivore that kills prey but has no scavenge link leaves
+        // someone else's catch alone
+        let omnivore_species = app
+            .world()
+            .resource::<SimulationConfig>()
+            .species
+            .id_of("omnivore")
+            .unwrap();
+        let position = app.world().get::<Transform>(prey).unwrap().translation;
+        let omnivore = app
             .world_mut()
-            .query_filtered::<Entity, With<Predator>>()
-            .iter(app.world())
-            .collect();
+            .spawn((
+                omnivore_species,
+                Omnivore,
+                Genome {
+                    speed: 0.0,
+                    size: 3.0,
+                    metabolism: 0.0,
+                    reproduction_threshold: 1000.0,
+                    vision_range: 50.0,
+                },
+                Energy(10.0),
+                Age(0.0),
+                Velocity(Vec2::ZERO),
+                HuntTarget(None),
+                ExplorationWaypoint {
+                    target: position.xy(),
+                    reached_threshold: 1.0,
+                },
+                Transform::from_translation(position),
+            ))
+            .id();
+        app.update();
+        assert_eq!(app.world().get::<Energy>(omnivore).unwrap().0, 10.0);
+    }
+
+    #[test]
+    fn test_hunters_finish_their_catch_first() {
+        let mut config = SimulationConfig {
+            world_size: Vec2::new(300.0, 300.0),
+            seed: Some(6),
+            ..default()
+        };
+        for species in &mut config.species.0 {
+            species.initial_count = 0;
+            species.immigration = None;
+            species.respawn = None;
+            species.reproduction_rate = 0.0;
+        }
+        let mut app = create_headless_app(config);
+        app.update();
+
+        // A predator on the omnivore it caught, with prey (its first kill
+        // link) within reach
+        let registry = app.world().resource::<SimulationConfig>().species.clone();
+        let genome = |size: f32| Genome {
+            speed: 0.0,
+            size,
+            metabolism: 0.0,
+            reproduction_threshold: 1000.0,
+            vision_range: 50.0,
+        };
+        let position = Vec3::new(150.0, 150.0, 0.0);
+        let catch = app
+            .world_mut()
+            .spawn((
+                genome(2.0),
+                Corpse::new(registry.id_of("omnivore").unwrap(), 30.0, 40.0),
+                Transform::from_translation(position),
+            ))
+            .id();
+        let prey = app
+            .world_mut()
+            .spawn((
+                registry.id_of("prey").unwrap(),
+                Forager,
+                genome(1.0),
+                Energy(100.0),
+                Age(0.0),
+                Velocity(Vec2::ZERO),
+                Stamina::default(),
+                Transform::from_translation(position),
+            ))
+            .id();
+        app.world_mut().spawn((
+            registry.id_of("predator").unwrap(),
+            Hunter,
+            genome(2.0),
+            Energy(10.0),
+            Age(0.0),
+            Velocity(Vec2::ZERO),
+            HuntTarget(Some(catch)),
+            ExplorationWaypoint {
+                target: position.xy(),
+                reached_threshold: 1.0,
+            },
+            Transform::from_translation(position),
+        ));
+
+        // It keeps eating the omnivore instead of killing the prey
+        app.update();
+        assert!(app.world().get::<Corpse>(prey).is_none());
+        assert!(app.world().get::<Corpse>(catch).unwrap().biomass < 40.0);
+    }
+
+    #[test]
+    fn test_corpse_biomass_rots_back_into_the_environment() {
+        let mut config = SimulationConfig {
+            corpse_decay_time: 2.0,
+            seed: Some(8),
+            ..default()
+        };
+        config.soil.waste_fraction = 0.0;
+        for species in &mut config.species.0 {
+            species.initial_count = 0;
+            species.immigration = None;
+            species.respawn = None;
+        }
+        // A starving prey still leaves a body worth eating
+        let prey = species_mut(&mut config, "prey");
+        prey.initial_count = 1;
+        prey.initial_energy = (0.5, 0.5);
+        prey.genome.size = (2.0, 2.0);
+        let biomass = 2.0 * prey.carcass_biomass;
 
-        // Kill all but 2 predators
-        for (i, entity) in predator_entities.iter().enumerate() {
-            if i >= 2 {
-                app.world_mut().despawn(*entity);
+        let mut app = create_headless_app(config);
+        app.update();
+        let prey = species_entities(&mut app, "prey")[0];
+        for _ in 0..60 {
+            app.update();
+        }
+        let corpse = app.world().get::<Corpse>(prey).unwrap();
+        assert!(corpse.biomass > biomass * 0.5 && corpse.biomass < biomass);
+        assert!(app.world().get::<Energy>(prey).is_none());
+
+        // Whatever nobody eats rots away over the decay time
+        for _ in 0..180 {
+            app.update();
+        }
+        assert!(app.world().get_entity(prey).is_err());
+        let soil = app.world().resource::<SoilNutrients>();
+        assert!(
+            (soil.deposited - biomass).abs() < 0.01,
+            "{}",
+            soil.deposited
+        );
+    }
+
+    #[test]
+    fn test_plants_draw_on_soil_nutrients() {
+        // Four soil cells, all barren except the top-right one
+        let mut config = SimulationConfig {
+            world_size: Vec2::new(400.0, 400.0),
+            seed: Some(4),
+            ..default()
+        };
+        config.soil.cell_size = 200.0;
+        config.soil.initial_nutrients = 0.0;
+        config.soil.waste_fraction = 0.0;
+        for species in &mut config.species.0 {
+            species.initial_count = 0;
+            species.immigration = None;
+            species.respawn = None;
+        }
+        let plant = species_mut(&mut config, "plant");
+        plant.initial_count = 12;
+        plant.initial_energy = (50.0, 50.0);
+        plant.reproduction_rate = 0.0;
+        plant.respawn = Some(Respawn {
+            rate: 20.0,
+            max_population: 40,
+        });
+        let rich = Vec2::new(100.0, 100.0);
+        let in_rich_cell = |position: Vec2| position.x >= 0.0 && position.y >= 0.0;
+
+        let mut app = create_headless_app(config);
+        app.update();
+        app.world_mut()
+            .resource_mut::<SoilNutrients>()
+            .deposit(rich, 1000.0);
+        let founders = species_entities(&mut app, "plant");
+        for _ in 0..120 {
+            app.update();
+        }
+
+        // Plants only grow where there are nutrients, taking them up as they do
+        let mut grew = 0;
+        for &founder in &founders {
+            let position = app.world().get::<Transform>(founder).unwrap().translation;
+            let energy = app.world().get::<Energy>(founder).unwrap().0;
+            if in_rich_cell(position.truncate()) {
+                assert!(energy > 50.0);
+                grew += 1;
+            } else {
+                assert_eq!(energy, 50.0);
             }
         }
+        assert!(grew > 0 && grew < founders.len());
+        let soil = app.world().resource::<SoilNutrients>();
+        assert!(soil.absorbed > 0.0);
+        assert!((soil.total() + soil.absorbed - 1000.0).abs() < 0.01);
+
+        // and new plants only take root in fertile soil
+        let plants = species_entities(&mut app, "plant");
+        assert!(plants.len() > founders.len());
+        for plant in plants {
+            let position = app.world().get::<Transform>(plant).unwrap().translation;
+            assert!(founders.contains(&plant) || in_rich_cell(position.truncate()));
+        }
+    }
+
+    #[test]
+    fn test_biomes_shape_growth_and_movement() {
+        // The map is the same for a seed, tiles the torus and has every biome
+        let config = SimulationConfig::default();
+        let map = BiomeMap::generate(config.world_size, &config.terrain, 1);
+        assert_eq!(
+            map,
+            BiomeMap::generate(config.world_size, &config.terrain, 1)
+        );
+        assert_ne!(
+            map,
+            BiomeMap::generate(config.world_size, &config.terrain, 2)
+        );
+        for biome in [Biome::Grassland, Biome::Forest, Biome::Desert, Biome::Marsh] {
+            assert!(map.cells().contains(&biome), "no {biome:?}");
+        }
+        let edge = config.world_size / 2.0;
+        assert_eq!(map.at(edge), map.at(-edge));
 
-        let scavenger_entities: Vec<Entity> = app
+        let mut config = SimulationConfig {
+            world_size: Vec2::new(400.0, 400.0),
+            seed: Some(6),
+            ..default()
+        };
+        config.terrain.cell_size = 200.0;
+        for species in &mut config.species.0 {
+            species.initial_count = 0;
+            species.immigration = None;
+            species.respawn = None;
+            species.reproduction_rate = 0.0;
+        }
+        let plant = species_mut(&mut config, "plant");
+        plant.initial_count = 12;
+        plant.initial_energy = (50.0, 50.0);
+        let scavenger = species_mut(&mut config, "scavenger");
+        scavenger.initial_count = 1;
+        scavenger.initial_energy = (500.0, 500.0);
+        scavenger.genome.speed = (100.0, 100.0);
+
+        // Forest on the right half, desert on the left
+        let mut app = create_headless_app(config.clone());
+        let mut map = BiomeMap::uniform(config.world_size, &config.terrain, Biome::Desert);
+        map.set(Vec2::new(100.0, 100.0), Biome::Forest);
+        map.set(Vec2::new(100.0, -100.0), Biome::Forest);
+        app.insert_resource(map);
+        for _ in 0..60 {
+            app.update();
+        }
+
+        // Plants grow faster in forest than in desert
+        let mut query = app
             .world_mut()
-            .query_filtered::<Entity, With<Scavenger>>()
+            .query_filtered::<(&Transform, &Energy), With<Producer>>();
+        let (forest, desert): (Vec<_>, Vec<_>) = query
             .iter(app.world())
-            .collect();
+            .map(|(transform, energy)| (transform.translation.x, energy.0))
+            .partition(|&(x, _)| x >= 0.0);
+        assert!(!forest.is_empty() && !desert.is_empty());
+        let slowest_forest = forest.iter().map(|&(_, e)| e).fold(f32::MAX, f32::min);
+        let fastest_desert = desert.iter().map(|&(_, e)| e).fold(0.0, f32::max);
+        assert!(slowest_forest > fastest_desert);
 
-        // Kill all but 2 scavengers
-        for (i, entity) in scavenger_entities.iter().enumerate() {
-            if i >= 2 {
-                app.world_mut().despawn(*entity);
-            }
+        // An exploring scavenger crosses marsh at its marsh speed
+        let mut app = create_headless_app(config.clone());
+        app.insert_resource(BiomeMap::uniform(
+            config.world_size,
+            &config.terrain,
+            Biome::Marsh,
+        ));
+        for _ in 0..120 {
+            app.update();
         }
+        let scavenger = species_entities(&mut app, "scavenger")[0];
+        let speed = app.world().get::<Velocity>(scavenger).unwrap().0.length();
+        let marsh_speed = 100.0 * config.terrain.marsh.speed;
+        assert!(
+            speed <= marsh_speed + 0.01 && speed > marsh_speed * 0.8,
+            "{speed}"
+        );
+    }
+
+    #[test]
+    fn test_seasons_shape_sunlight_growth_and_metabolism() {
+        let config = ClimateConfig::default();
+        // Noon is halfway through the sun being up, which is longer in summer
+        let noon = |year_progress: f32, sun_up: f32| {
+            let day = (year_progress * config.year_length / config.day_length).floor();
+            (day + sun_up / 2.0) * config.day_length
+        };
+        let mut summer = Climate::new(&config);
+        summer.update(noon(0.375, 0.75), &config);
+        let mut winter = Climate::new(&config);
+        winter.update(noon(0.875, 0.45), &config);
+        assert_eq!(summer.season, Season::Summer);
+        assert_eq!(winter.season, Season::Winter);
+        assert!(summer.sunlight(&config) > winter.sunlight(&config));
+        assert!(summer.temperature > winter.temperature + 20.0);
+        assert_eq!(summer.metabolism, 1.0);
+        assert!(winter.metabolism > 1.0);
 
+        // Plants stop growing on a winter night
+        let mut night = winter.clone();
+        night.update(noon(0.875, 0.45) + config.day_length * 0.4, &config);
+        assert_eq!(night.daylight, 0.0);
+        assert!(night.sunlight(&config) > 0.0);
+        assert_eq!(night.growth, 0.0);
+
+        // A frozen world starves its plants while a mild one feeds them
+        let plant_energy = |mean_temperature: f32| {
+            let mut config = SimulationConfig {
+                seed: Some(8),
+                ..default()
+            };
+            for species in &mut config.species.0 {
+                species.initial_count = 0;
+                species.immigration = None;
+                species.respawn = None;
+            }
+            let plant = species_mut(&mut config, "plant");
+            plant.initial_count = 10;
+            plant.initial_energy = (50.0, 50.0);
+            config.climate.mean_temperature = mean_temperature;
+            config.climate.temperature_variation = 0.0;
+            let mut app = create_headless_app(config);
+            for _ in 0..60 {
+                app.update();
+            }
+            let mut query = app.world_mut().query_filtered::<&Energy, With<Producer>>();
+            query.iter(app.world()).map(|energy| energy.0).sum::<f32>()
+        };
+        assert_eq!(plant_energy(-20.0), 500.0);
+        assert!(plant_energy(20.0) > 500.0);
+
+        // Every year after the first is a drought when droughts are certain
+        let mut config = SimulationConfig {
+            seed: Some(8),
+            ..default()
+        };
+        config.climate.year_length = 0.5;
+        config.climate.drought_chance = 1.0;
+        config.climate.wet_chance = 0.0;
+        let mut app = create_headless_app(config);
         app.update();
-        let stats_before = app.world().resource::<PopulationStats>().clone();
-        println!(
-            "Before immigration - Prey: {}, Predators: {}, Scavengers: {}",
-            stats_before.prey, stats_before.predators, stats_before.scavengers
+        assert_eq!(
+            app.world().resource::<Climate>().year_kind,
+            YearKind::Normal
         );
+        for _ in 0..40 {
+            app.update();
+        }
+        let climate = app.world().resource::<Climate>();
+        assert!(climate.year > 0);
+        assert_eq!(climate.year_kind, YearKind::Drought);
+    }
 
-        // Run for 120 seconds to allow immigration to trigger multiple times
-        // At 2% chance per second, we should see immigration events
-        for _ in 0..7200 {
+    #[test]
+    fn test_night_dims_vision_and_rests_foragers() {
+        // Day animals see less after dark; nocturnal ones see more
+        let night = NightConfig::default();
+        assert_eq!(night.vision(0.0, false), 1.0);
+        assert_eq!(night.vision(0.0, true), 1.0);
+        assert!(night.vision(1.0, false) < 1.0);
+        assert!(night.vision(1.0, true) > 1.0);
+
+        let mut climate = Climate::new(&ClimateConfig::default());
+        assert_eq!(climate.hour(), 6.0);
+        climate.update(4.0, &ClimateConfig::default());
+        assert!(!climate.is_night());
+        climate.update(16.0, &ClimateConfig::default());
+        assert!(climate.is_night());
+        assert_eq!(climate.darkness(), 1.0);
+
+        // Well-fed foragers slow to a rest once the sun is down
+        let average_speed = |elapsed: f32| {
+            let mut config = SimulationConfig {
+                seed: Some(4),
+                ..default()
+            };
+            for species in &mut config.species.0 {
+                species.initial_count = 0;
+                species.immigration = None;
+                species.respawn = None;
+                species.reproduction_rate = 0.0;
+            }
+            let prey = species_mut(&mut config, "prey");
+            prey.initial_count = 20;
+            prey.initial_energy = (500.0, 500.0);
+            let mut app = create_headless_app(config);
+            app.insert_resource(SimulationClock { elapsed });
+            for _ in 0..60 {
+                app.update();
+            }
+            let mut query = app.world_mut().query::<&Velocity>();
+            let speeds: Vec<f32> = query
+                .iter(app.world())
+                .map(|velocity| velocity.0.length())
+                .collect();
+            speeds.iter().sum::<f32>() / speeds.len() as f32
+        };
+        let day = average_speed(4.0);
+        let night = average_speed(16.0);
+        assert!(night < day * 0.5, "night {night}, day {day}");
+    }
+
+    #[test]
+    fn test_blind_animals_still_explore() {
+        // Born blind, and blinder still at night
+        let mut config = SimulationConfig {
+            world_size: Vec2::new(400.0, 400.0),
+            seed: Some(9),
+            ..default()
+        };
+        config.night.vision = 0.0;
+        for species in &mut config.species.0 {
+            species.initial_count = 0;
+            species.immigration = None;
+            species.reproduction_rate = 0.0;
+            species.genome.vision_range = (0.0, 0.0);
+        }
+        for name in ["prey", "predator", "scavenger", "omnivore"] {
+            species_mut(&mut config, name).initial_count = 2;
+        }
+        let mut app = create_headless_app(config);
+        for _ in 0..120 {
             app.update();
         }
+        assert_eq!(
+            app.world().resource::<PopulationStats>().count("predator"),
+            2
+        );
+    }
 
-        let stats_after = app.world().resource::<PopulationStats>().clone();
-        println!(
-            "After 120s - Prey: {}, Predators: {}, Scavengers: {}",
-            stats_after.prey, stats_after.predators, stats_after.scavengers
+    #[test]
+    fn test_obstacles_keep_organisms_out() {
+        // A lake in the middle of a small, crowded world
+        let lake = Obstacle {
+            kind: ObstacleKind::Lake,
+            center: Vec2::ZERO,
+            radius: 200.0,
+        };
+        let mut config = SimulationConfig {
+            world_size: Vec2::new(800.0, 800.0),
+            seed: Some(12),
+            ..default()
+        };
+        config.obstacles.placed = vec![lake];
+
+        // Heading straight at the shore turns into following it
+        let obstacles = Obstacles::generate(config.world_size, &config.obstacles, 0);
+        let shore = Vec2::new(-210.0, 0.0);
+        let steered = obstacles.steer(shore, Vec2::X, 40.0);
+        assert!(steered.x <= 0.0 && steered.y.abs() > 0.5, "{steered}");
+        assert!(obstacles.contains(Vec2::new(150.0, 0.0)));
+        assert!(!obstacles.contains(obstacles.nearest_open(Vec2::new(150.0, 0.0))));
+
+        let mut app = create_headless_app(config);
+        let mut query = app.world_mut().query_filtered::<&Transform, With<Genome>>();
+        for _ in 0..300 {
+            app.update();
+            for transform in query.iter(app.world()) {
+                let position = transform.translation.truncate();
+                assert!(
+                    position.length() >= lake.radius,
+                    "{position} is in the lake"
+                );
+            }
+        }
+        assert!(species_entities(&mut app, "prey").len() > 10);
+    }
+
+    #[test]
+    fn test_flow_fields_route_around_obstacles() {
+        let mut config = ObstacleConfig {
+            placed: vec![Obstacle {
+                kind: ObstacleKind::Lake,
+                center: Vec2::ZERO,
+                radius: 200.0,
+            }],
+            ..default()
+        };
+        config.lake_density = 0.0;
+        config.rock_density = 0.0;
+        let world_size = Vec2::new(2000.0, 1000.0);
+        let obstacles = Obstacles::generate(world_size, &config, 0);
+        let paths = FlowFields::new(&obstacles, &PathfindingConfig::default());
+
+        // The short way across the world edge is clear, so no search is needed
+        let heading = paths.heading(Vec2::new(-950.0, 0.0), Vec2::new(950.0, 0.0));
+        assert!(heading.abs_diff_eq(-Vec2::X, 1e-6), "{heading}");
+        assert_eq!(paths.cached_fields(), 0);
+
+        // Across the lake, following the headings walks around it
+        let goal = Vec2::new(300.0, 0.0);
+        let mut position = Vec2::new(-300.0, 0.0);
+        let mut steps = 0;
+        while position.distance(goal) > 10.0 {
+            let mut translation = (position + paths.heading(position, goal) * 10.0).extend(0.0);
+            wrap_position(&mut translation, &world_size);
+            position = translation.truncate();
+            assert!(!obstacles.contains(position), "{position} is in the lake");
+            steps += 1;
+            assert!(steps < 150, "stuck at {position}");
+        }
+        assert_eq!(paths.cached_fields(), 1);
+    }
+
+    #[test]
+    fn test_food_web_drives_feeding() {
+        let config = SimulationConfig::default();
+        let registry = &config.species;
+        let id = |name| registry.id_of(name).unwrap();
+        let web = &config.food_web;
+        assert_eq!(
+            web.diet(registry, id("predator")),
+            vec![id("prey"), id("omnivore")]
+        );
+        assert_eq!(
+            web.threats(registry, id("prey")),
+            vec![id("omnivore"), id("predator")]
+        );
+        assert_eq!(
+            web.carrion(registry, id("scavenger")),
+            vec![id("prey"), id("omnivore"), id("predator"), id("apex")]
         );
+        assert!(web.threats(registry, id("scavenger")).is_empty());
 
-        // Immigration should have brought populations back up
-        assert!(
-            stats_after.prey >= 2,
-            "Prey population didn't recover via immigration ({})",
-            stats_after.prey
+        // One new link lets predators hunt scavengers, which now flee them
+        let mut config = SimulationConfig {
+            world_size: Vec2::new(300.0, 300.0),
+            seed: Some(9),
+            ..default()
+        };
+        config.food_web.0.push(FoodLink {
+            consumer: "predator".into(),
+            resource: "scavenger".into(),
+            mode: FeedingMode::Kill,
+            radius: 20.0,
+            efficiency: 0.7,
+            bite: 480.0,
+            min_energy: 0.0,
+            max_relative_size: None,
+        });
+        for species in &mut config.species.0 {
+            species.initial_count = 0;
+            species.immigration = None;
+            species.respawn = None;
+            species.reproduction_rate = 0.0;
+        }
+        species_mut(&mut config, "predator").initial_count = 4;
+        species_mut(&mut config, "scavenger").initial_count = 12;
+        config.validate().unwrap();
+        let registry = &config.species;
+        assert_eq!(
+            config
+                .food_web
+                .threats(registry, registry.id_of("scavenger").unwrap()),
+            vec![registry.id_of("predator").unwrap()]
         );
+
+        // Non-numbers are as invalid as negative numbers
+        let mut broken = config.clone();
+        let link = broken.food_web.0.last_mut().unwrap();
+        link.bite = f32::NAN;
+        link.radius = f32::NAN;
+        link.min_energy = -1.0;
+        let Err(ConfigError::Invalid(problems)) = broken.validate() else {
+            panic!("Invalid food web passed validation");
+        };
+        assert_eq!(problems.len(), 3, "Unexpected problems: {problems:?}");
+        for field in ["bite", "radius", "min_energy"] {
+            assert!(problems.iter().any(|p| p.contains(field)));
+        }
+
+        let mut app = create_headless_app(config);
+        for _ in 0..600 {
+            app.update();
+        }
+        let stats = app.world().resource::<PopulationStats>();
+        assert_eq!(stats.count("predator"), 4);
+        assert!(stats.count("scavenger") < 12);
+
+        // Grazing takes bites but leaves the plant alive, as fast at any
+        // tick rate
+        let grazed = |tick_rate: f64, bite: f32| {
+            let mut config = SimulationConfig {
+                world_size: Vec2::new(100.0, 100.0),
+                tick_rate,
+                seed: Some(2),
+                ..default()
+            };
+            for species in &mut config.species.0 {
+                species.initial_count = 0;
+                species.immigration = None;
+                species.respawn = None;
+                species.reproduction_rate = 0.0;
+            }
+            let plant = species_mut(&mut config, "plant");
+            plant.initial_count = 1;
+            plant.initial_energy = (100.0, 100.0);
+            plant.photosynthesis = 0.0;
+            species_mut(&mut config, "prey").initial_count = 1;
+            config.food_web.0[0].radius = 200.0;
+            config.food_web.0[0].bite = bite;
+            let mut app = create_headless_app(config);
+            for _ in 0..(tick_rate / 2.0) as usize {
+                app.update();
+            }
+            let plants = species_entities(&mut app, "plant");
+            assert_eq!(plants.len(), 1);
+            app.world().get::<Energy>(plants[0]).unwrap().0
+        };
+        let energy = grazed(60.0, 1800.0);
+        assert!(energy < 20.0, "{energy}");
+        let (slow, fast) = (grazed(30.0, 60.0), grazed(120.0, 60.0));
         assert!(
-            stats_after.predators >= 2,
-            "Predator population didn't recover via immigration ({})",
-            stats_after.predators
+            slow < 100.0 && (slow - fast).abs() < 5.0,
+            "{slow} vs {fast}"
         );
+    }
+
+    #[test]
+    fn test_genome_mutation() {
+        use rand::SeedableRng;
+        let mut rng = rand::rngs::StdRng::seed_from_u64(5);
+        let registry = SpeciesRegistry::default();
+        let species = |name| &registry.get(registry.id_of(name).unwrap()).genome;
+        let parent = species("prey").sample(&mut rng);
+
+        let frozen = MutationConfig {
+            speed: TraitMutation::new(0.0, 0.5, 10.0, 400.0),
+            size: TraitMutation::new(0.0, 0.5, 0.2, 6.0),
+            metabolism: TraitMutation::new(0.0, 0.5, 0.1, 4.0),
+            reproduction_threshold: TraitMutation::new(0.0, 0.5, 20.0, 300.0),
+            vision_range: TraitMutation::new(0.0, 0.5, 20.0, 400.0),
+        };
+        assert_eq!(parent.mutated(&frozen, &mut rng), parent);
+
+        // Always mutate, with tight bounds so clamping is exercised
+        let mut always = frozen.clone();
+        always.speed = TraitMutation::new(1.0, 0.5, 90.0, 110.0);
+        always.vision_range = TraitMutation::new(1.0, 0.5, 20.0, 400.0);
+        let mut lineage = parent.clone();
+        for _ in 0..100 {
+            lineage = lineage.mutated(&always, &mut rng);
+            assert!((90.0..=110.0).contains(&lineage.speed));
+            assert!((20.0..=400.0).contains(&lineage.vision_range));
+        }
+        assert_ne!(lineage.vision_range, parent.vision_range);
+        assert_eq!(lineage.size, parent.size);
+
+        // Plants never gain speed or vision
+        let plant = species("plant").sample(&mut rng);
+        let offspring = plant.mutated(&always, &mut rng);
+        assert_eq!((offspring.speed, offspring.vision_range), (0.0, 0.0));
+
+        let mut invalid = SimulationConfig::default();
+        invalid.mutation.size = TraitMutation::new(1.5, -0.1, 5.0, 1.0);
+        let Err(ConfigError::Invalid(problems)) = invalid.validate() else {
+            panic!("Invalid mutation settings passed validation");
+        };
+        assert_eq!(problems.len(), 3, "Unexpected problems: {problems:?}");
+    }
+
+    #[test]
+    fn test_sexual_reproduction_needs_a_mate() {
+        let run = |separation: f32| {
+            let mut config = SimulationConfig {
+                sexual_reproduction: true,
+                mating_energy_cost: 0.3,
+                seed: Some(11),
+                ..default()
+            };
+            for species in &mut config.species.0 {
+                species.initial_count = 0;
+                species.respawn = None;
+                species.immigration = None;
+            }
+            species_mut(&mut config, "prey").reproduction_rate = 1000.0;
+            let prey = config.species.id_of("prey").unwrap();
+
+            let mut app = create_headless_app(config);
+            for (x, speed) in [(0.0, 50.0), (separation, 150.0)] {
+                app.world_mut().spawn((
+                    prey,
+                    Forager,
+                    Genome {
+                        speed,
+                        size: 1.0,
+                        metabolism: 0.0,
+                        reproduction_threshold: 80.0,
+                        vision_range: 100.0,
+                    },
+                    Energy(100.0),
+                    Age(0.0),
+                    Velocity(Vec2::ZERO),
+                    Stamina::default(),
+                    Transform::from_xyz(x, 0.0, 1.0),
+                ));
+            }
+            for _ in 0..10 {
+                app.update();
+            }
+
+            let mut prey = app.world_mut().query_filtered::<&Genome, With<Forager>>();
+            prey.iter(app.world()).cloned().collect::<Vec<_>>()
+        };
+
+        // Too far apart to find each other
+        assert_eq!(run(1000.0).len(), 2);
+
+        // Neighbours mate exactly once, after which both are below threshold
+        let prey = run(30.0);
+        assert_eq!(prey.len(), 3);
         assert!(
-            stats_after.scavengers >= 2,
-            "Scavenger population didn't recover via immigration ({})",
-            stats_after.scavengers
+            prey.iter()
+                .all(|genome| (50.0..=150.0).contains(&genome.speed)),
+            "Offspring traits should come from its parents"
         );
+    }
 
-        // At least one species should have increased from immigration
-        let total_before = stats_before.prey + stats_before.predators + stats_before.scavengers;
-        let total_after = stats_after.prey + stats_after.predators + stats_after.scavengers;
-        assert!(
-            total_after >= total_before,
-            "No population recovery occurred (before: {}, after: {})",
-            total_before,
-            total_after
+    #[test]
+    fn test_offspring_may_be_born_in_place() {
+        let mut config = SimulationConfig {
+            seed: Some(4),
+            ..default()
+        };
+        for species in &mut config.species.0 {
+            species.initial_count = 0;
+            species.respawn = None;
+            species.immigration = None;
+        }
+        let prey = species_mut(&mut config, "prey");
+        prey.reproduction_rate = 1000.0;
+        prey.offspring_spread = 0.0;
+        let prey = config.species.id_of("prey").unwrap();
+
+        let mut app = create_headless_app(config);
+        app.world_mut().spawn((
+            prey,
+            Forager,
+            Genome {
+                speed: 0.0,
+                size: 1.0,
+                metabolism: 0.0,
+                reproduction_threshold: 80.0,
+                vision_range: 100.0,
+            },
+            Energy(200.0),
+            Age(0.0),
+            Velocity(Vec2::ZERO),
+            Stamina::default(),
+            Transform::from_xyz(0.0, 0.0, 1.0),
+        ));
+        for _ in 0..10 {
+            app.update();
+        }
+
+        let mut prey = app
+            .world_mut()
+            .query_filtered::<&Transform, With<Forager>>();
+        let positions: Vec<Vec2> = prey.iter(app.world()).map(|t| t.translation.xy()).collect();
+        assert!(positions.len() > 1);
+        assert!(positions.iter().all(|&position| position == Vec2::ZERO));
+    }
+
+    #[test]
+    fn test_lineage_tracking() {
+        let mut app = create_headless_app(SimulationConfig {
+            seed: Some(21),
+            ..default()
+        });
+        for _ in 0..600 {
+            app.update();
+        }
+
+        let mut query = app.world_mut().query::<&Lineage>();
+        let lineages: Vec<Lineage> = query.iter(app.world()).cloned().collect();
+        let mut ids: Vec<u64> = lineages.iter().map(|l| l.id).collect();
+        ids.sort_unstable();
+        ids.dedup();
+        assert_eq!(ids.len(), lineages.len(), "Lineage ids must be unique");
+
+        for lineage in &lineages {
+            if lineage.founder {
+                assert_eq!(lineage.generation, 0);
+                assert!(lineage.parents.is_empty());
+            } else {
+                assert!(lineage.generation >= 1);
+                assert!(!lineage.parents.is_empty());
+                // Parents always exist before their offspring
+                assert!(lineage.parents.iter().all(|&parent| parent < lineage.id));
+            }
+        }
+        assert!(lineages.iter().any(|l| !l.founder && l.birth_time > 0.0));
+
+        let history = app.world().resource::<SimulationHistory>();
+        let latest = history
+            .snapshots
+            .last()
+            .expect("history should be recorded");
+        let generations: Vec<GenerationStats> =
+            latest.species.iter().map(|s| s.generations).collect();
+        assert_eq!(generations.len(), 6);
+        assert!(generations.iter().any(|g| g.max >= 1 && g.mean > 0.0));
+        assert!(generations.iter().all(|g| g.mean <= g.max as f32));
+    }
+
+    #[test]
+    fn test_ledger_and_newick_export() {
+        let mut app = create_headless_app(SimulationConfig {
+            seed: Some(8),
+            ..default()
+        });
+        for _ in 0..600 {
+            app.update();
+        }
+
+        let mut living = app
+            .world_mut()
+            .query_filtered::<&Lineage, Without<Corpse>>();
+        let living_ids: Vec<u64> = living.iter(app.world()).map(|l| l.id).collect();
+
+        let world = app.world();
+        let ledger = world.resource::<BirthDeathLedger>();
+        let next_id = world.resource::<LineageTracker>().next_id;
+        assert_eq!(
+            ledger.entries.len() as u64,
+            next_id,
+            "Every organism is recorded"
         );
+        for id in &living_ids {
+            assert_eq!(ledger.entries[id].death_time, None);
+        }
+        let dead = ledger
+            .entries
+            .values()
+            .filter(|entry| entry.death_time.is_some())
+            .count();
+        assert_eq!(dead, ledger.entries.len() - living_ids.len());
+        assert!(dead > 0, "Some organisms should have died (been eaten)");
+
+        let newick = ledger.to_newick(&world.resource::<SimulationConfig>().species);
+        assert!(newick.ends_with("run;"));
+        assert_eq!(newick.matches('(').count(), newick.matches(')').count());
+        assert_eq!(newick.matches("[&&NHX:").count(), ledger.entries.len());
+        assert!(newick.contains(":speed="));
+        assert!(newick.contains("[&&NHX:S=prey:"));
+    }
+
+    #[test]
+    fn test_spatial_grid_wraps_around_edges() {
+        let mut grid = SpatialGrid::default();
+        grid.reset(Vec2::new(1000.0, 600.0), 90.0, 2);
+        let prey = GridLayer::Living(SpeciesId(0));
+
+        let mut world = World::new();
+        let near_left = world.spawn_empty().id();
+        let near_bottom = world.spawn_empty().id();
+        let far = world.spawn_empty().id();
+        grid.insert(prey, near_left, Vec2::new(-495.0, 0.0));
+        grid.insert(prey, near_bottom, Vec2::new(490.0, -295.0));
+        grid.insert(prey, far, Vec2::new(0.0, 0.0));
+
+        // Close to the right edge, so both wrapped neighbours are in reach
+        let found: Vec<Entity> = grid
+            .within(prey, Vec2::new(495.0, 295.0), 20.0)
+            .map(|(entity, _, _)| entity)
+            .collect();
+        assert_eq!(found, [near_bottom]);
+        let found: Vec<(Entity, f32)> = grid
+            .within(prey, Vec2::new(490.0, 5.0), 20.0)
+            .map(|(entity, _, distance)| (entity, distance))
+            .collect();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, near_left);
+        assert!((found[0].1 - 250.0_f32.sqrt()).abs() < 1e-3);
+
+        // Layers are separate, and a radius bigger than the world visits each cell once
+        let others = [
+            GridLayer::Corpse(SpeciesId(0)),
+            GridLayer::Living(SpeciesId(1)),
+        ];
+        assert_eq!(grid.within_any(&others, Vec2::ZERO, 5000.0).count(), 0);
+        assert_eq!(grid.within(prey, Vec2::ZERO, 5000.0).count(), 3);
+        let nearest = grid.nearest(&[prey], Vec2::new(10.0, 0.0), 5000.0, |e| e != far);
+        assert_eq!(nearest.map(|(entity, _, _)| entity), Some(near_left));
+    }
+
+    #[test]
+    fn test_config_hot_reload() {
+        let path = std::env::temp_dir().join(format!(
+            "predators-and-prey-reload-{}.ron",
+            std::process::id()
+        ));
+        std::fs::write(&path, "(max_age: 300.0, seed: Some(1))").unwrap();
+
+        let config = SimulationConfig::load(&path).unwrap();
+        let mut app = create_headless_app(config.clone());
+        app.add_plugins(ConfigHotReloadPlugin { path: path.clone() });
+        app.update();
+
+        // Live fields apply, including edits to existing species;
+        // restart-only fields keep the running world's values
+        let mut edited = SimulationConfig {
+            max_age: 200.0,
+            world_size: Vec2::new(100.0, 100.0),
+            ..config.clone()
+        };
+        species_mut(&mut edited, "prey").reproduction_rate = 0.45;
+        edited.food_web.0[1].efficiency = 0.5;
+        std::fs::write(&path, ron::to_string(&edited).unwrap()).unwrap();
+        for _ in 0..90 {
+            app.update();
+        }
+
+        let running = app.world().resource::<SimulationConfig>().clone();
+        assert_eq!(running.max_age, 200.0);
+        let prey = running.species.id_of("prey").unwrap();
+        assert_eq!(running.species.get(prey).reproduction_rate, 0.45);
+        assert_eq!(running.food_web.0[1].efficiency, 0.5);
+        assert_eq!(running.world_size, config.world_size);
+
+        // Adding a species needs a restart; invalid edits are ignored
+        let mut added = running.clone();
+        added.species.0.push(SpeciesConfig {
+            name: "beetle".into(),
+            ..default()
+        });
+        added.max_age = 150.0;
+        std::fs::write(&path, ron::to_string(&added).unwrap()).unwrap();
+        for _ in 0..90 {
+            app.update();
+        }
+        std::fs::write(&path, "(max_age: -1.0)").unwrap();
+        for _ in 0..90 {
+            app.update();
+        }
+        let config = app.world().resource::<SimulationConfig>();
+        assert_eq!(config.max_age, 150.0);
+        assert_eq!(config.species, running.species);
+
+        std::fs::remove_file(&path).ok();
+    }
+
+    #[test]
+    fn test_config_changed_fields() {
+        let old = SimulationConfig::default();
+        let new = SimulationConfig {
+            max_age: 200.0,
+            world_size: Vec2::new(100.0, 100.0),
+            ..default()
+        };
+
+        let changes = old.changed_fields(&new);
+        let names: Vec<&str> = changes.iter().map(|c| c.field.as_str()).collect();
+        assert_eq!(names, ["max_age", "world_size"]);
+        assert!(!changes[0].requires_restart());
+        assert!(changes[1].requires_restart());
+        assert!(old.changed_fields(&old).is_empty());
+
+        // Soil tuning applies live, but not a new cell size
+        let mut new = old.clone();
+        new.soil.uptake = 0.8;
+        assert!(!old.changed_fields(&new)[0].requires_restart());
+        new.soil.cell_size = 50.0;
+        assert!(old.changed_fields(&new)[0].requires_restart());
+        let mut live = old.clone();
+        live.apply_live(&new);
+        assert_eq!(live.soil.uptake, 0.8);
+        assert_eq!(live.soil.cell_size, old.soil.cell_size);
+
+        // Likewise avoidance, but not where the obstacles are
+        let mut new = old.clone();
+        new.obstacles.avoidance_distance = 60.0;
+        assert!(!old.changed_fields(&new)[0].requires_restart());
+        new.obstacles.rock_density = 2.0;
+        assert!(old.changed_fields(&new)[0].requires_restart());
+
+        // Likewise biome effects, but not the map layout
+        let mut new = old.clone();
+        new.terrain.forest.speed = 0.5;
+        assert!(!old.changed_fields(&new)[0].requires_restart());
+        new.terrain.patch_size = 400.0;
+        assert!(old.changed_fields(&new)[0].requires_restart());
+    }
+
+    /// Everything a save should preserve, sorted so entity order does not matter
+    fn sorted_state(app: &mut App) -> Vec<(u32, u32, u32, u32, bool)> {
+        let mut query = app.world_mut().query::<(
+            &Transform,
+            Option<&Energy>,
+            Option<&Corpse>,
+            &Age,
+            Option<&HuntTarget>,
+        )>();
+        let world = app.world();
+        let mut state: Vec<_> = query
+            .iter(world)
+            .map(|(transform, energy, corpse, age, target)| {
+                (
+                    transform.translation.x.to_bits(),
+                    transform.translation.y.to_bits(),
+                    food_value(energy, corpse).to_bits(),
+                    age.0.to_bits(),
+                    // Saves drop targets that no longer exist
+                    target
+                        .and_then(|t| t.0)
+                        .is_some_and(|target| world.get_entity(target).is_ok()),
+                )
+            })
+            .collect();
+        state.sort_unstable();
+        state
+    }
+
+    #[test]
+    fn test_save_round_trip() {
+        let config = SimulationConfig {
+            seed: Some(7),
+            ..default()
+        };
+        let mut original = create_headless_app(config.clone());
+        for _ in 0..600 {
+            original.update();
+        }
+
+        let save = SaveFile::capture(original.world_mut());
+        let text = save.to_ron().expect("save should serialize");
+        let parsed: SaveFile = ron::from_str(&text).expect("save should parse");
+        assert_eq!(parsed, save);
+
+        // Load into an app that was started with a different seed
+        let mut loaded = create_headless_app(SimulationConfig {
+            seed: Some(99),
+            ..config
+        });
+        loaded.insert_resource(PendingWorldLoad(parsed));
+        loaded.update();
+
+        assert!(!loaded.world().contains_resource::<PendingWorldLoad>());
+        assert_eq!(
+            loaded.world().resource::<SimulationRng>().seed(),
+            save.rng_seed
+        );
+        assert!(sorted_state(&mut loaded) == sorted_state(&mut original));
+    }
+
+    #[test]
+    fn test_save_rejects_other_versions() {
+        let mut app = create_headless_app(SimulationConfig {
+            seed: Some(1),
+            ..default()
+        });
+        app.update();
+
+        let mut save = SaveFile::capture(app.world_mut());
+        save.version = SAVE_VERSION + 1;
+        let path = std::env::temp_dir().join(format!("save-version-{}.ron", std::process::id()));
+        save.write(&path).unwrap();
+
+        let result = SaveFile::read(&path);
+        std::fs::remove_file(&path).ok();
+        assert!(matches!(
+            result,
+            Err(SaveError::UnsupportedVersion(version)) if version == SAVE_VERSION + 1
+        ));
+    }
+
+    #[test]
+    fn test_save_rejects_invalid_contents() {
+        let mut app = create_headless_app(SimulationConfig {
+            seed: Some(1),
+            ..default()
+        });
+        app.update();
+        let save = SaveFile::capture(app.world_mut());
+        let path = std::env::temp_dir().join(format!("save-contents-{}.ron", std::process::id()));
+        let read_back = |save: &SaveFile| {
+            save.write(&path).unwrap();
+            let result = SaveFile::read(&path);
+            std::fs::remove_file(&path).ok();
+            result
+        };
+
+        // A hand-edited config is validated like a config file
+        let mut broken = save.clone();
+        broken.config.corpse_decay_time = -1.0;
+        assert!(matches!(
+            read_back(&broken),
+            Err(SaveError::InvalidConfig(ConfigError::Invalid(_)))
+        ));
+
+        // Organisms of species the config does not define are not spawned
+        let mut broken = save.clone();
+        let species = SpeciesId(broken.config.species.len());
+        broken.organisms[0].species = Some(species);
+        assert!(matches!(
+            read_back(&broken),
+            Err(SaveError::UnknownSpecies { organism: 0, species: id }) if id == species
+        ));
+
+        assert!(read_back(&save).is_ok());
     }
 }
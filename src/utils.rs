@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 // ===== UTILITY FUNCTIONS =====
 
@@ -42,3 +43,66 @@ pub fn wrapped_direction(from: Vec2, to: Vec2, world_size: &Vec2) -> Vec2 {
 pub fn wrapped_distance(from: Vec2, to: Vec2, world_size: &Vec2) -> f32 {
     wrapped_direction(from, to, world_size).length()
 }
+
+/// Probability that an event with the given per-second rate fires during one
+/// tick of length `dt`, clamped to a valid probability
+pub fn chance_per_tick(rate_per_second: f32, dt: f32) -> f64 {
+    (rate_per_second * dt).clamp(0.0, 1.0) as f64
+}
+
+/// The SplitMix64 finaliser: scrambles `z` so that nearby inputs give
+/// unrelated outputs
+pub fn splitmix64(mut z: u64) -> u64 {
+    z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    z ^ (z >> 31)
+}
+
+/// How a coarse per-cell map divides the torus world. Cells are stretched
+/// slightly so a whole number of them spans the world, and are numbered row
+/// by row from the bottom-left corner.
+#[derive(Clone, Copy, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CellLayout {
+    #[serde(with = "crate::config::vec2_as_array")]
+    pub world_size: Vec2,
+    #[serde(with = "crate::config::vec2_as_array")]
+    pub cell_size: Vec2,
+    pub columns: usize,
+    pub rows: usize,
+}
+
+impl CellLayout {
+    /// Cells of roughly `cell_size` on each side
+    pub fn new(world_size: Vec2, cell_size: f32) -> Self {
+        let columns = ((world_size.x / cell_size) as usize).max(1);
+        let rows = ((world_size.y / cell_size) as usize).max(1);
+        Self {
+            world_size,
+            cell_size: world_size / Vec2::new(columns as f32, rows as f32),
+            columns,
+            rows,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.columns * self.rows
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Index of the cell containing `position`, wrapping around the edges
+    pub fn index_of(&self, position: Vec2) -> usize {
+        let cell = ((position + self.world_size / 2.0) / self.cell_size).floor();
+        let column = (cell.x as i64).rem_euclid(self.columns as i64) as usize;
+        let row = (cell.y as i64).rem_euclid(self.rows as i64) as usize;
+        row * self.columns + column
+    }
+
+    /// World position of the centre of cell `index`
+    pub fn center(&self, index: usize) -> Vec2 {
+        let (column, row) = (index % self.columns, index / self.columns);
+        (Vec2::new(column as f32, row as f32) + 0.5) * self.cell_size - self.world_size / 2.0
+    }
+}